@@ -0,0 +1,16 @@
+use std::path::Path;
+
+/// The bundle the `embedded-redoc` feature builds into the package; it is not committed.
+const EMBEDDED_REDOC: &str = "vendor/redoc.standalone.js";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", EMBEDDED_REDOC);
+    if std::env::var_os("CARGO_FEATURE_EMBEDDED_REDOC").is_some() && !Path::new(EMBEDDED_REDOC).is_file() {
+        eprintln!(
+            "error: the embedded-redoc feature needs the Redoc bundle at {}, download it first:\n\
+             curl -o {} --create-dirs https://cdn.jsdelivr.net/npm/redoc@2.1.3/bundles/redoc.standalone.js",
+            EMBEDDED_REDOC, EMBEDDED_REDOC
+        );
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,450 @@
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Document, Element, Event, HtmlElement, HtmlInputElement};
+use crate::dom::{self, Listeners};
+use crate::openapi::{ApiKeyLocation, OAuthFlow, SecurityScheme};
+use crate::options::{AuthBtnOptions, OAuth2Options};
+use super::oauth2::{self, FlowKind, OAuthClient};
+use super::{Auth, Credential};
+
+const DEFAULT_AUTH_TEXT: &str = "Authorize";
+
+/// The Authorize button and the dialog it opens.
+pub struct AuthDialog {
+    document: Document,
+    auth: Rc<Auth>,
+    oauth2: OAuth2Options,
+    button: HtmlElement,
+    overlay: HtmlElement,
+    listeners: Listeners,
+}
+
+impl AuthDialog {
+    /// Insert the Authorize button at `pos_selector`, or after the API title in `container`.
+    pub fn mount(
+        document: &Document,
+        config: &AuthBtnOptions,
+        oauth2: &OAuth2Options,
+        container: &Element,
+        auth: Rc<Auth>,
+    ) -> Result<Option<Rc<AuthDialog>>, JsValue> {
+        if auth.schemes().is_empty() {
+            return Ok(None);
+        }
+
+        let button = dom::text(
+            document,
+            "button",
+            "try-it-out-auth-btn",
+            config.text.as_deref().unwrap_or(DEFAULT_AUTH_TEXT),
+        )?;
+        button.set_attribute("type", "button")?;
+        if let Some(class_name) = &config.class_name {
+            dom::add_classes(&button, class_name)?;
+        }
+        match &config.pos_selector {
+            Some(selector) => document
+                .query_selector(selector)?
+                .ok_or_else(|| JsValue::from_str(&format!("no element matches authBtn.posSelector {}", selector)))?
+                .append_child(&button)
+                .map(|_| ())?,
+            None => match container.query_selector("h1")? {
+                Some(title) => dom::insert_after(&title, &button)?,
+                None => {
+                    container.prepend_with_node_1(&button)?;
+                }
+            },
+        }
+
+        let overlay = dom::create(document, "div", "try-it-out-auth-overlay")?;
+        let dialog = Rc::new(AuthDialog {
+            document: document.clone(),
+            auth,
+            oauth2: oauth2.clone(),
+            button,
+            overlay,
+            listeners: Listeners::default(),
+        });
+
+        let this = Rc::clone(&dialog);
+        dialog.listeners.add(&dialog.button, "click", move |_| {
+            if let Err(e) = this.open() {
+                web_sys::console::error_1(&e);
+            }
+        })?;
+
+        // the dialog is re-rendered after every change, so its events are delegated to the overlay
+        let this = Rc::clone(&dialog);
+        dialog.listeners.add(&dialog.overlay, "submit", move |event: Event| {
+            event.prevent_default();
+            if let Err(e) = this.on_submit(&event) {
+                web_sys::console::error_1(&e);
+            }
+        })?;
+
+        let this = Rc::clone(&dialog);
+        dialog.listeners.add(&dialog.overlay, "click", move |event: Event| {
+            if let Err(e) = this.on_click(&event) {
+                web_sys::console::error_1(&e);
+            }
+        })?;
+
+        dialog.discover();
+        Ok(Some(dialog))
+    }
+
+    /// Fetch the discovery documents of the openIdConnect schemes in the background and show
+    /// their flows once they arrived, so a slow identity provider doesn't hold up the page.
+    fn discover(self: &Rc<Self>) {
+        for (name, scheme) in self.auth.schemes() {
+            if !matches!(scheme, SecurityScheme::OpenIdConnect { .. }) {
+                continue;
+            }
+            let this = Rc::clone(self);
+            let name = name.clone();
+            spawn_local(async move {
+                this.auth.discover(&name).await;
+                if this.overlay.is_connected() {
+                    if let Err(e) = this.render() {
+                        web_sys::console::error_1(&e);
+                    }
+                }
+            });
+        }
+    }
+
+    fn open(&self) -> Result<(), JsValue> {
+        self.render()?;
+        self.document
+            .body()
+            .ok_or_else(|| JsValue::from_str("should have a body"))?
+            .append_child(&self.overlay)?;
+        Ok(())
+    }
+
+    fn close(&self) {
+        self.overlay.remove();
+    }
+
+    /// Remove the button and the dialog and their listeners.
+    pub fn unmount(&self) {
+        self.listeners.clear();
+        self.close();
+        self.button.remove();
+    }
+
+    fn on_submit(self: &Rc<Self>, event: &Event) -> Result<(), JsValue> {
+        let Some(form) = event.target().and_then(|target| target.dyn_into::<Element>().ok()) else {
+            return Ok(());
+        };
+        let Some(name) = form.get_attribute("data-scheme") else {
+            return Ok(());
+        };
+        let Some(scheme) = self.auth.schemes().get(&name) else {
+            return Ok(());
+        };
+
+        let credential = match scheme {
+            SecurityScheme::ApiKey { .. } => Credential::ApiKey(input(&form, "value")?),
+            SecurityScheme::Http { scheme, .. } if scheme.eq_ignore_ascii_case("basic") => Credential::Basic {
+                username: input(&form, "username")?,
+                password: input(&form, "password")?,
+            },
+            SecurityScheme::Http { .. } => Credential::Bearer(input(&form, "value")?),
+            SecurityScheme::OAuth2 { .. } | SecurityScheme::OpenIdConnect { .. } => {
+                let flows = self.auth.flows(&name).map_err(|e| JsValue::from_str(&e))?;
+                let kind = form.get_attribute("data-flow");
+                let Some((kind, flow)) = flows
+                    .available()
+                    .into_iter()
+                    .find(|(available, _)| Some(available.key()) == kind.as_deref())
+                else {
+                    return Ok(());
+                };
+                return self.authorize_oauth2(name, kind, flow.clone(), form);
+            }
+            SecurityScheme::Unsupported => return Ok(()),
+        };
+        self.auth.authorize(&name, credential);
+        self.render()
+    }
+
+    /// Run an oauth2 flow in the background, reporting failures inside `form`.
+    fn authorize_oauth2(self: &Rc<Self>, name: String, kind: FlowKind, flow: OAuthFlow, form: Element) -> Result<(), JsValue> {
+        let client = OAuthClient {
+            client_id: input(&form, "client_id")?,
+            client_secret: Some(input(&form, "client_secret")?).filter(|secret| !secret.is_empty()),
+        };
+        let scopes = dom::query_all(&form, "input[name=\"scope\"]")?
+            .into_iter()
+            .filter_map(|scope| scope.dyn_into::<HtmlInputElement>().ok())
+            .filter(|scope| scope.checked())
+            .map(|scope| scope.value())
+            .collect::<Vec<_>>();
+        let redirect_uri = match oauth2::redirect_url(self.oauth2.redirect_url.as_deref()) {
+            Ok(url) => url,
+            Err(e) => return show_error(&self.document, &form, &e),
+        };
+        let use_pkce = self.oauth2.use_pkce;
+
+        let this = Rc::clone(self);
+        spawn_local(async move {
+            match oauth2::authorize(kind, &flow, &client, &scopes, &redirect_uri, use_pkce).await {
+                Ok(token) => {
+                    let refresh_url = flow.refresh_url.or(flow.token_url);
+                    this.auth.authorize(&name, Credential::OAuth2 { token, client, refresh_url });
+                    if let Err(e) = this.render() {
+                        web_sys::console::error_1(&e);
+                    }
+                }
+                Err(e) => {
+                    let message = e.as_string().unwrap_or_else(|| format!("{:?}", e));
+                    if let Err(e) = show_error(&this.document, &form, &message) {
+                        web_sys::console::error_1(&e);
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn on_click(&self, event: &Event) -> Result<(), JsValue> {
+        let Some(target) = event.target().and_then(|target| target.dyn_into::<Element>().ok()) else {
+            return Ok(());
+        };
+        if target == *self.overlay {
+            self.close();
+            return Ok(());
+        }
+        match target.get_attribute("data-action").as_deref() {
+            Some("close") => self.close(),
+            Some("logout") => {
+                if let Some(name) = target.get_attribute("data-scheme") {
+                    self.auth.logout(&name);
+                    self.render()?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn render(&self) -> Result<(), JsValue> {
+        let document = &self.document;
+        self.overlay.set_inner_html("");
+
+        let dialog = dom::create(document, "div", "try-it-out-auth-dialog")?;
+        dialog.set_attribute("role", "dialog")?;
+        let header = dom::create(document, "div", "try-it-out-auth-header")?;
+        let title = dom::text(document, "h3", "", "Available authorizations")?;
+        header.append_child(&title)?;
+        let close = dom::text(document, "button", "try-it-out-auth-close", "×")?;
+        close.set_attribute("type", "button")?;
+        close.set_attribute("data-action", "close")?;
+        header.append_child(&close)?;
+        dialog.append_child(&header)?;
+
+        for (name, scheme) in self.auth.schemes() {
+            let form = self.render_scheme(name, scheme)?;
+            dialog.append_child(&form)?;
+        }
+
+        self.overlay.append_child(&dialog)?;
+        Ok(())
+    }
+
+    fn render_scheme(&self, name: &str, scheme: &SecurityScheme) -> Result<HtmlElement, JsValue> {
+        let document = &self.document;
+        let section = dom::create(document, "div", "try-it-out-auth-scheme")?;
+
+        let heading = dom::text(document, "h4", "", &format!("{} ({})", name, scheme_label(scheme)))?;
+        section.append_child(&heading)?;
+        if let Some(description) = scheme.description() {
+            let description = dom::text(document, "p", "try-it-out-auth-description", description)?;
+            section.append_child(&description)?;
+        }
+
+        if let Some(credential) = self.auth.credential(name) {
+            let status = match &credential {
+                Credential::OAuth2 { token, .. } => match token.expires_in(oauth2::now()) {
+                    Some(seconds) => format!("Authorized, the token expires in {} s", seconds.max(0)),
+                    None => "Authorized".to_string(),
+                },
+                _ => "Authorized".to_string(),
+            };
+            let status = dom::text(document, "p", "try-it-out-auth-status", &status)?;
+            section.append_child(&status)?;
+            let logout = dom::text(document, "button", "try-it-out-auth-logout", "Logout")?;
+            logout.set_attribute("type", "button")?;
+            logout.set_attribute("data-action", "logout")?;
+            logout.set_attribute("data-scheme", name)?;
+            section.append_child(&logout)?;
+            return Ok(section);
+        }
+
+        match scheme {
+            SecurityScheme::ApiKey { name: key_name, .. } => {
+                let form = self.scheme_form(name)?;
+                let key = labelled_input(document, key_name, "value", "text", "")?;
+                form.append_child(&key)?;
+                self.finish_form(&section, &form)?;
+            }
+            SecurityScheme::Http { scheme, .. } if scheme.eq_ignore_ascii_case("basic") => {
+                let form = self.scheme_form(name)?;
+                let username = labelled_input(document, "Username", "username", "text", "")?;
+                form.append_child(&username)?;
+                let password = labelled_input(document, "Password", "password", "password", "")?;
+                form.append_child(&password)?;
+                self.finish_form(&section, &form)?;
+            }
+            SecurityScheme::Http { .. } => {
+                let form = self.scheme_form(name)?;
+                let token = labelled_input(document, "Token", "value", "text", "")?;
+                form.append_child(&token)?;
+                self.finish_form(&section, &form)?;
+            }
+            SecurityScheme::OAuth2 { .. } | SecurityScheme::OpenIdConnect { .. } => {
+                if let SecurityScheme::OpenIdConnect { open_id_connect_url, .. } = scheme {
+                    let discovery = format!("Discovery: {}", open_id_connect_url);
+                    let url = dom::text(document, "p", "try-it-out-auth-url", &discovery)?;
+                    section.append_child(&url)?;
+                }
+                let flows = match self.auth.flows(name) {
+                    Ok(flows) => flows,
+                    Err(e) => {
+                        let error = dom::text(document, "p", "try-it-out-auth-error", &e)?;
+                        section.append_child(&error)?;
+                        return Ok(section);
+                    }
+                };
+                let available = flows.available();
+                if available.is_empty() {
+                    let note = dom::text(document, "p", "", "None of the oauth2 flows is supported by try it out.")?;
+                    section.append_child(&note)?;
+                }
+                for (kind, flow) in available {
+                    let form = self.oauth2_form(name, kind, flow)?;
+                    self.finish_form(&section, &form)?;
+                }
+            }
+            SecurityScheme::Unsupported => {
+                let note = dom::text(document, "p", "", "This security scheme is not supported by try it out.")?;
+                section.append_child(&note)?;
+            }
+        }
+        Ok(section)
+    }
+
+    fn scheme_form(&self, name: &str) -> Result<HtmlElement, JsValue> {
+        let form = dom::create(&self.document, "form", "try-it-out-auth-form")?;
+        form.set_attribute("data-scheme", name)?;
+        Ok(form)
+    }
+
+    fn finish_form(&self, section: &Element, form: &Element) -> Result<(), JsValue> {
+        let submit = dom::text(&self.document, "button", "try-it-out-auth-submit", "Authorize")?;
+        submit.set_attribute("type", "submit")?;
+        form.append_child(&submit)?;
+        section.append_child(form)?;
+        Ok(())
+    }
+
+    fn oauth2_form(&self, name: &str, kind: FlowKind, flow: &OAuthFlow) -> Result<HtmlElement, JsValue> {
+        let document = &self.document;
+        let form = self.scheme_form(name)?;
+        form.set_attribute("data-flow", kind.key())?;
+
+        let title = dom::text(document, "h5", "", kind.label())?;
+        form.append_child(&title)?;
+        for (label, url) in [("Authorization URL", &flow.authorization_url), ("Token URL", &flow.token_url)] {
+            if let Some(url) = url {
+                let line = dom::text(document, "p", "try-it-out-auth-url", &format!("{}: {}", label, url))?;
+                form.append_child(&line)?;
+            }
+        }
+
+        let client_id = self.oauth2.client_id.as_deref().unwrap_or_default();
+        let client_id = labelled_input(document, "Client ID", "client_id", "text", client_id)?;
+        form.append_child(&client_id)?;
+        let client_secret = self.oauth2.client_secret.as_deref().unwrap_or_default();
+        let client_secret = labelled_input(document, "Client secret", "client_secret", "password", client_secret)?;
+        form.append_child(&client_secret)?;
+
+        if !flow.scopes.is_empty() {
+            let scopes = dom::create(document, "fieldset", "try-it-out-auth-scopes")?;
+            let legend = dom::text(document, "legend", "", "Scopes")?;
+            scopes.append_child(&legend)?;
+            for (scope, description) in &flow.scopes {
+                let label = dom::create(document, "label", "try-it-out-auth-scope")?;
+                let checkbox = dom::create(document, "input", "")?;
+                checkbox.set_attribute("type", "checkbox")?;
+                checkbox.set_attribute("name", "scope")?;
+                checkbox.set_attribute("value", scope)?;
+                checkbox.set_attribute("checked", "")?;
+                label.append_child(&checkbox)?;
+                let text = dom::text(document, "span", "", &format!("{} – {}", scope, description))?;
+                label.append_child(&text)?;
+                scopes.append_child(&label)?;
+            }
+            form.append_child(&scopes)?;
+        }
+        Ok(form)
+    }
+}
+
+fn scheme_label(scheme: &SecurityScheme) -> String {
+    match scheme {
+        SecurityScheme::ApiKey { location, .. } => {
+            let location = match location {
+                ApiKeyLocation::Header => "header",
+                ApiKeyLocation::Query => "query",
+                ApiKeyLocation::Cookie => "cookie",
+            };
+            format!("apiKey in {}", location)
+        }
+        SecurityScheme::Http { scheme, bearer_format, .. } => match bearer_format {
+            Some(format) => format!("http {}, {}", scheme, format),
+            None => format!("http {}", scheme),
+        },
+        SecurityScheme::OAuth2 { .. } => "oauth2".to_string(),
+        SecurityScheme::OpenIdConnect { .. } => "openIdConnect".to_string(),
+        SecurityScheme::Unsupported => "unsupported".to_string(),
+    }
+}
+
+fn labelled_input(
+    document: &Document,
+    label: &str,
+    name: &str,
+    input_type: &str,
+    value: &str,
+) -> Result<HtmlElement, JsValue> {
+    let field = dom::create(document, "label", "try-it-out-field")?;
+    let caption = dom::text(document, "span", "try-it-out-label", label)?;
+    field.append_child(&caption)?;
+    let input = dom::create(document, "input", "")?;
+    input.set_attribute("name", name)?;
+    input.set_attribute("type", input_type)?;
+    input.set_attribute("autocomplete", "off")?;
+    input.set_attribute("value", value)?;
+    field.append_child(&input)?;
+    Ok(field)
+}
+
+fn input(form: &Element, name: &str) -> Result<String, JsValue> {
+    Ok(form
+        .query_selector(&format!("[name=\"{}\"]", name))?
+        .ok_or_else(|| JsValue::from_str(&format!("should have a {} input", name)))?
+        .dyn_into::<HtmlInputElement>()?
+        .value())
+}
+
+fn show_error(document: &Document, form: &Element, message: &str) -> Result<(), JsValue> {
+    if let Some(previous) = form.query_selector(".try-it-out-auth-error")? {
+        previous.remove();
+    }
+    let error = dom::text(document, "p", "try-it-out-auth-error", message)?;
+    form.append_child(&error)?;
+    Ok(())
+}
@@ -0,0 +1,372 @@
+mod dialog;
+mod oauth2;
+mod oidc;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use wasm_bindgen::JsValue;
+use super::http::HttpRequest;
+use super::openapi::{ApiKeyLocation, OAuthFlows, SecurityRequirement, SecurityScheme, Spec};
+use super::try_it_out::OperationTarget;
+use oauth2::{Grant, OAuthClient, Token};
+
+pub use dialog::AuthDialog;
+
+/// A value entered in the Authorize dialog.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Credential {
+    ApiKey(String),
+    Basic { username: String, password: String },
+    /// The credentials of any other `http` scheme, sent after the scheme name.
+    Bearer(String),
+    OAuth2 {
+        token: Token,
+        client: OAuthClient,
+        /// Where the token is refreshed, `refreshUrl` or else `tokenUrl`.
+        refresh_url: Option<String>,
+    },
+}
+
+/// Security schemes and requirements of a spec together with the credentials
+/// entered for them.
+#[derive(Debug)]
+pub struct Auth {
+    spec: Rc<Spec>,
+    /// Flows of openIdConnect schemes, or why their discovery failed.
+    discovered: RefCell<BTreeMap<String, Result<OAuthFlows, String>>>,
+    credentials: RefCell<BTreeMap<String, Credential>>,
+}
+
+impl Auth {
+    pub fn new(spec: Rc<Spec>) -> Auth {
+        Auth {
+            spec,
+            discovered: RefCell::default(),
+            credentials: RefCell::default(),
+        }
+    }
+
+    /// Fetch the discovery document of the openIdConnect scheme `name`.
+    pub async fn discover(&self, name: &str) {
+        let Some(SecurityScheme::OpenIdConnect { open_id_connect_url, .. }) = self.schemes().get(name) else {
+            return;
+        };
+        let flows = oidc::discover(&self.spec.url, open_id_connect_url)
+            .await
+            .map(|discovery| discovery.flows())
+            .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)));
+        self.discovered.borrow_mut().insert(name.to_string(), flows);
+    }
+
+    /// The oauth2 flows of `scheme`, declared in the spec or discovered.
+    pub fn flows(&self, scheme: &str) -> Result<OAuthFlows, String> {
+        match self.schemes().get(scheme) {
+            Some(SecurityScheme::OAuth2 { flows, .. }) => Ok(flows.as_ref().clone()),
+            Some(SecurityScheme::OpenIdConnect { .. }) => self
+                .discovered
+                .borrow()
+                .get(scheme)
+                .cloned()
+                .unwrap_or_else(|| Err("the discovery document is still loading".to_string())),
+            _ => Err(format!("{} is not an oauth2 scheme", scheme)),
+        }
+    }
+
+    pub fn schemes(&self) -> &indexmap::IndexMap<String, SecurityScheme> {
+        &self.spec.api.components.security_schemes
+    }
+
+    pub fn credential(&self, scheme: &str) -> Option<Credential> {
+        self.credentials.borrow().get(scheme).cloned()
+    }
+
+    pub fn authorize(&self, scheme: &str, credential: Credential) {
+        self.credentials.borrow_mut().insert(scheme.to_string(), credential);
+    }
+
+    pub fn logout(&self, scheme: &str) {
+        self.credentials.borrow_mut().remove(scheme);
+    }
+
+    /// Refresh expired oauth2 tokens, dropping the ones that cannot be refreshed.
+    pub async fn refresh_expired(&self) {
+        let now = oauth2::now();
+        let expired: Vec<_> = self
+            .credentials
+            .borrow()
+            .iter()
+            .filter_map(|(scheme, credential)| match credential {
+                Credential::OAuth2 { token, client, refresh_url } if token.is_expired(now) => {
+                    Some((scheme.clone(), token.clone(), client.clone(), refresh_url.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        for (scheme, token, client, refresh_url) in expired {
+            let (Some(refresh_token), Some(url)) = (&token.refresh_token, &refresh_url) else {
+                self.logout(&scheme);
+                continue;
+            };
+            match oauth2::request_token(url, &client, &Grant::RefreshToken { refresh_token }).await {
+                Ok(mut refreshed) => {
+                    // servers may omit the refresh token when it does not rotate
+                    if refreshed.refresh_token.is_none() {
+                        refreshed.refresh_token = token.refresh_token.clone();
+                    }
+                    self.authorize(&scheme, Credential::OAuth2 { token: refreshed, client, refresh_url });
+                }
+                Err(e) => {
+                    web_sys::console::warn_2(&JsValue::from_str(&format!("Failed to refresh {}:", scheme)), &e);
+                    self.logout(&scheme);
+                }
+            }
+        }
+    }
+
+    /// The requirements of `target`, falling back to the global ones.
+    fn requirements(&self, target: &OperationTarget) -> &[SecurityRequirement] {
+        match self.spec.find_operation_by_section_id(&target.section_id) {
+            Some(operation) => operation.security(&self.spec),
+            None => &self.spec.api.security,
+        }
+    }
+
+    /// Apply the credentials of the first requirement of `target` that is fully authorized.
+    pub fn apply(&self, target: &OperationTarget, request: &mut HttpRequest) {
+        for (scheme, credential) in self.applicable(target) {
+            apply_credential(&scheme, &credential, request);
+        }
+    }
+
+    /// The values `apply` puts into a request for `target` that should not be shared.
+    pub fn secrets(&self, target: &OperationTarget) -> Vec<String> {
+        self.applicable(target)
+            .into_iter()
+            .flat_map(|(_, credential)| match credential {
+                Credential::ApiKey(key) => vec![key],
+                Credential::Basic { username, password } => {
+                    vec![STANDARD.encode(format!("{}:{}", username, password)), password]
+                }
+                Credential::Bearer(token) => vec![token],
+                Credential::OAuth2 { token, .. } => vec![token.access_token],
+            })
+            .collect()
+    }
+
+    /// The schemes and credentials of the first requirement of `target` that is fully authorized.
+    /// An empty requirement, which makes auth optional, is always satisfied and so only used
+    /// when no other one is.
+    fn applicable(&self, target: &OperationTarget) -> Vec<(SecurityScheme, Credential)> {
+        let credentials = self.credentials.borrow();
+        let satisfied = self.requirements(target).iter().find(|requirement| {
+            !requirement.is_empty() && requirement.keys().all(|scheme| credentials.contains_key(scheme))
+        });
+        let Some(requirement) = satisfied else {
+            return Vec::new();
+        };
+        requirement
+            .keys()
+            .filter_map(|scheme_name| {
+                let scheme = self.schemes().get(scheme_name)?;
+                let credential = credentials.get(scheme_name)?;
+                Some((scheme.clone(), credential.clone()))
+            })
+            .collect()
+    }
+}
+
+fn apply_credential(scheme: &SecurityScheme, credential: &Credential, request: &mut HttpRequest) {
+    match (scheme, credential) {
+        (SecurityScheme::ApiKey { name, location, .. }, Credential::ApiKey(key)) => match location {
+            ApiKeyLocation::Header => request.set_header(name, key),
+            ApiKeyLocation::Query => request.query.push((name.clone(), key.clone())),
+            ApiKeyLocation::Cookie => request.cookies.push((name.clone(), key.clone())),
+        },
+        (SecurityScheme::Http { .. }, Credential::Basic { username, password }) => {
+            let encoded = STANDARD.encode(format!("{}:{}", username, password));
+            request.set_header("Authorization", &format!("Basic {}", encoded));
+        }
+        (SecurityScheme::Http { scheme, .. }, Credential::Bearer(token)) => {
+            let scheme = if scheme.eq_ignore_ascii_case("bearer") {
+                "Bearer"
+            } else {
+                scheme.as_str()
+            };
+            request.set_header("Authorization", &format!("{} {}", scheme, token));
+        }
+        (SecurityScheme::OAuth2 { .. } | SecurityScheme::OpenIdConnect { .. }, Credential::OAuth2 { token, .. }) => {
+            let token_type = if token.token_type.eq_ignore_ascii_case("bearer") {
+                "Bearer"
+            } else {
+                token.token_type.as_str()
+            };
+            request.set_header("Authorization", &format!("{} {}", token_type, token.access_token));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    fn spec() -> Rc<Spec> {
+        let document = serde_json::from_str(
+            r###"
+            {
+                "openapi": "3.0.3",
+                "security": [{ "api_key": [] }, { "petstore_auth": ["read:pets"] }],
+                "paths": {
+                    "/pet/{petId}": {
+                        "get": { "operationId": "getPetById" },
+                        "put": { "operationId": "updatePet", "security": [{}, { "api_key": [] }] },
+                        "delete": {
+                            "operationId": "deletePet",
+                            "security": [{ "basic": [] }, { "bearer": [], "session": [] }]
+                        }
+                    }
+                },
+                "components": {
+                    "securitySchemes": {
+                        "api_key": { "type": "apiKey", "name": "X-Api-Key", "in": "header" },
+                        "session": { "type": "apiKey", "name": "session", "in": "cookie" },
+                        "basic": { "type": "http", "scheme": "basic" },
+                        "bearer": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                        "petstore_auth": {
+                            "type": "oauth2",
+                            "flows": {
+                                "clientCredentials": {
+                                    "tokenUrl": "https://auth.example.com/token",
+                                    "scopes": { "read:pets": "read your pets" }
+                                }
+                            }
+                        },
+                        "mutual": { "type": "mutualTLS" }
+                    }
+                }
+            }
+        "###,
+        )
+        .unwrap();
+        Rc::new(Spec::new("https://example.com/openapi.json", document).unwrap())
+    }
+
+    fn target(operation_id: &str, method: &str) -> OperationTarget {
+        OperationTarget {
+            section_id: format!("tag/pet/operation/{}", operation_id),
+            method: method.to_string(),
+            path: "/pet/{petId}".to_string(),
+        }
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_security_schemes_from_document() {
+        let auth = Auth::new(spec());
+
+        assert_eq!(
+            Some(&SecurityScheme::ApiKey {
+                name: "X-Api-Key".to_string(),
+                location: ApiKeyLocation::Header,
+                description: None,
+            }),
+            auth.schemes().get("api_key")
+        );
+        assert_eq!(Some(&SecurityScheme::Unsupported), auth.schemes().get("mutual"));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_apply_global_requirement() {
+        let auth = Auth::new(spec());
+        auth.authorize("api_key", Credential::ApiKey("secret".to_string()));
+
+        let mut request = HttpRequest::new("GET", "/pet/1");
+        auth.apply(&target("getPetById", "GET"), &mut request);
+
+        assert_eq!(vec![("X-Api-Key".to_string(), "secret".to_string())], request.headers);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_apply_http_scheme_name() {
+        let cases = [("bearer", "Bearer t0k3n"), ("BEARER", "Bearer t0k3n"), ("Token", "Token t0k3n")];
+        for (scheme, expected) in cases {
+            let scheme = SecurityScheme::Http {
+                scheme: scheme.to_string(),
+                bearer_format: None,
+                description: None,
+            };
+            let mut request = HttpRequest::new("GET", "/pet/1");
+            apply_credential(&scheme, &Credential::Bearer("t0k3n".to_string()), &mut request);
+
+            assert_eq!(vec![("Authorization".to_string(), expected.to_string())], request.headers);
+        }
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_apply_optional_requirement() {
+        let auth = Auth::new(spec());
+
+        let mut request = HttpRequest::new("PUT", "/pet/1");
+        auth.apply(&target("updatePet", "PUT"), &mut request);
+        assert!(request.headers.is_empty());
+
+        auth.authorize("api_key", Credential::ApiKey("secret".to_string()));
+        let mut request = HttpRequest::new("PUT", "/pet/1");
+        auth.apply(&target("updatePet", "PUT"), &mut request);
+        assert_eq!(vec![("X-Api-Key".to_string(), "secret".to_string())], request.headers);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_apply_first_satisfied_requirement() {
+        let auth = Auth::new(spec());
+        auth.authorize("api_key", Credential::ApiKey("secret".to_string()));
+        auth.authorize("bearer", Credential::Bearer("token".to_string()));
+        auth.authorize("session", Credential::ApiKey("abc".to_string()));
+
+        let mut request = HttpRequest::new("DELETE", "/pet/1");
+        auth.apply(&target("deletePet", "DELETE"), &mut request);
+
+        assert_eq!(vec![("Authorization".to_string(), "Bearer token".to_string())], request.headers);
+        assert_eq!(vec![("session".to_string(), "abc".to_string())], request.cookies);
+
+        auth.authorize("basic", Credential::Basic {
+            username: "user".to_string(),
+            password: "pass".to_string(),
+        });
+        let mut request = HttpRequest::new("DELETE", "/pet/1");
+        auth.apply(&target("deletePet", "DELETE"), &mut request);
+
+        assert_eq!(vec![("Authorization".to_string(), "Basic dXNlcjpwYXNz".to_string())], request.headers);
+        assert!(request.cookies.is_empty());
+        assert_eq!(
+            vec!["dXNlcjpwYXNz".to_string(), "pass".to_string()],
+            auth.secrets(&target("deletePet", "DELETE"))
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_apply_oauth2_token() {
+        let auth = Auth::new(spec());
+        auth.authorize("petstore_auth", Credential::OAuth2 {
+            token: Token {
+                access_token: "t1".to_string(),
+                token_type: "bearer".to_string(),
+                refresh_token: None,
+                expires_at: None,
+                scope: None,
+            },
+            client: OAuthClient::default(),
+            refresh_url: None,
+        });
+
+        let mut request = HttpRequest::new("GET", "/pet/1");
+        auth.apply(&target("getPetById", "GET"), &mut request);
+
+        assert_eq!(vec![("Authorization".to_string(), "Bearer t1".to_string())], request.headers);
+    }
+}
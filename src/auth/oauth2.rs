@@ -0,0 +1,600 @@
+use std::collections::BTreeMap;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{js_sys, window, MessageEvent, Window};
+use crate::http::{self, decode_component, encode_component, Body, HttpRequest, HttpResponse};
+use crate::openapi::{resolve_url, OAuthFlow, OAuthFlows};
+
+/// `type` of the message posted by `oauth2-redirect.html`.
+const REDIRECT_MESSAGE: &str = "redoc-try-it-out-oauth2";
+/// Tokens are refreshed a little before they actually expire.
+const EXPIRY_SKEW_MS: f64 = 30_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlowKind {
+    AuthorizationCode,
+    ClientCredentials,
+    Implicit,
+}
+
+impl FlowKind {
+    pub fn key(&self) -> &'static str {
+        match self {
+            FlowKind::AuthorizationCode => "authorizationCode",
+            FlowKind::ClientCredentials => "clientCredentials",
+            FlowKind::Implicit => "implicit",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FlowKind::AuthorizationCode => "Authorization code with PKCE",
+            FlowKind::ClientCredentials => "Client credentials",
+            FlowKind::Implicit => "Implicit",
+        }
+    }
+}
+
+impl OAuthFlows {
+    /// The flows try it out can run, in order of preference.
+    pub fn available(&self) -> Vec<(FlowKind, &OAuthFlow)> {
+        [
+            (FlowKind::AuthorizationCode, &self.authorization_code),
+            (FlowKind::ClientCredentials, &self.client_credentials),
+            (FlowKind::Implicit, &self.implicit),
+        ]
+        .into_iter()
+        .filter_map(|(kind, flow)| flow.as_ref().map(|flow| (kind, flow)))
+        .collect()
+    }
+}
+
+/// The client registration entered in the Authorize dialog.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+}
+
+/// An access token together with what is needed to refresh it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub access_token: String,
+    pub token_type: String,
+    pub refresh_token: Option<String>,
+    /// Expiry as milliseconds since the epoch.
+    pub expires_at: Option<f64>,
+    pub scope: Option<String>,
+}
+
+impl Token {
+    pub fn is_expired(&self, now: f64) -> bool {
+        self.expires_at
+            .map(|expires_at| now + EXPIRY_SKEW_MS >= expires_at)
+            .unwrap_or(false)
+    }
+
+    /// Seconds left before the token expires.
+    pub fn expires_in(&self, now: f64) -> Option<i64> {
+        self.expires_at.map(|expires_at| ((expires_at - now) / 1000.0).floor() as i64)
+    }
+
+    fn from_params(params: &BTreeMap<String, String>, now: f64) -> Option<Token> {
+        Some(Token {
+            access_token: params.get("access_token")?.clone(),
+            token_type: params.get("token_type").cloned().unwrap_or_else(|| "Bearer".to_string()),
+            refresh_token: params.get("refresh_token").cloned(),
+            expires_at: params
+                .get("expires_in")
+                .and_then(|expires_in| expires_in.parse::<f64>().ok())
+                .map(|expires_in| now + expires_in * 1000.0),
+            scope: params.get("scope").cloned(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    token_type: Option<String>,
+    expires_in: Option<f64>,
+    refresh_token: Option<String>,
+    scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+impl ErrorResponse {
+    fn message(&self) -> String {
+        match &self.error_description {
+            Some(description) => format!("{}: {}", self.error, description),
+            None => self.error.clone(),
+        }
+    }
+}
+
+/// A PKCE code verifier built from `random` bytes (RFC 7636 section 4.1).
+pub fn code_verifier(random: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(random)
+}
+
+/// The S256 code challenge of `verifier` (RFC 7636 section 4.2).
+pub fn code_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Parameters of the authorization endpoint request.
+pub struct AuthorizationRequest<'a> {
+    pub response_type: &'a str,
+    pub client: &'a OAuthClient,
+    pub redirect_uri: &'a str,
+    pub scopes: &'a [String],
+    pub state: &'a str,
+    pub code_challenge: Option<&'a str>,
+}
+
+/// The url the user is sent to in order to grant access.
+pub fn authorization_url(endpoint: &str, request: &AuthorizationRequest) -> String {
+    let mut params = vec![
+        ("response_type", request.response_type.to_string()),
+        ("client_id", request.client.client_id.clone()),
+        ("redirect_uri", request.redirect_uri.to_string()),
+        ("state", request.state.to_string()),
+    ];
+    if !request.scopes.is_empty() {
+        params.push(("scope", request.scopes.join(" ")));
+    }
+    if let Some(challenge) = request.code_challenge {
+        params.push(("code_challenge", challenge.to_string()));
+        params.push(("code_challenge_method", "S256".to_string()));
+    }
+    let separator = if endpoint.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", endpoint, separator, form_encode(&params))
+}
+
+pub enum Grant<'a> {
+    AuthorizationCode {
+        code: &'a str,
+        redirect_uri: &'a str,
+        code_verifier: Option<&'a str>,
+    },
+    ClientCredentials {
+        scopes: &'a [String],
+    },
+    RefreshToken {
+        refresh_token: &'a str,
+    },
+}
+
+/// The token endpoint request for `grant`, authenticating the client in the body.
+pub fn token_request(token_url: &str, client: &OAuthClient, grant: &Grant) -> HttpRequest {
+    let mut params = match grant {
+        Grant::AuthorizationCode { code, redirect_uri, code_verifier } => {
+            let mut params = vec![
+                ("grant_type", "authorization_code".to_string()),
+                ("code", code.to_string()),
+                ("redirect_uri", redirect_uri.to_string()),
+            ];
+            if let Some(verifier) = code_verifier {
+                params.push(("code_verifier", verifier.to_string()));
+            }
+            params
+        }
+        Grant::ClientCredentials { scopes } => {
+            let mut params = vec![("grant_type", "client_credentials".to_string())];
+            if !scopes.is_empty() {
+                params.push(("scope", scopes.join(" ")));
+            }
+            params
+        }
+        Grant::RefreshToken { refresh_token } => vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token.to_string()),
+        ],
+    };
+    params.push(("client_id", client.client_id.clone()));
+    if let Some(secret) = client.client_secret.as_ref().filter(|secret| !secret.is_empty()) {
+        params.push(("client_secret", secret.clone()));
+    }
+
+    let mut request = HttpRequest::new("POST", token_url);
+    request.set_header("Content-Type", "application/x-www-form-urlencoded");
+    request.set_header("Accept", "application/json");
+    request.body = Some(Body::Text(form_encode(&params)));
+    request
+}
+
+/// Read a token endpoint response (RFC 6749 sections 5.1 and 5.2).
+pub fn parse_token_response(response: &HttpResponse, now: f64) -> Result<Token, String> {
+    if let Ok(error) = serde_json::from_str::<ErrorResponse>(&response.body) {
+        return Err(error.message());
+    }
+    if response.status >= 400 {
+        return Err(format!("token endpoint answered {} {}", response.status, response.status_text));
+    }
+    let token: TokenResponse = serde_json::from_str(&response.body)
+        .map_err(|e| format!("unexpected token response: {}", e))?;
+    Ok(Token {
+        access_token: token.access_token,
+        token_type: token.token_type.unwrap_or_else(|| "Bearer".to_string()),
+        refresh_token: token.refresh_token,
+        expires_at: token.expires_in.map(|expires_in| now + expires_in * 1000.0),
+        scope: token.scope,
+    })
+}
+
+/// What the authorization server sent back to the redirect page.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Redirect {
+    Code(String),
+    Token(Token),
+}
+
+/// Read the query and fragment of the redirect url, checking `state`.
+pub fn parse_redirect(url: &str, expected_state: &str, now: f64) -> Result<Redirect, String> {
+    let mut params = BTreeMap::new();
+    let (url, fragment) = url.split_once('#').unwrap_or((url, ""));
+    let query = url.split_once('?').map(|(_, query)| query).unwrap_or_default();
+    for pair in query.split('&').chain(fragment.split('&')).filter(|pair| !pair.is_empty()) {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        params.insert(decode_component(name), decode_component(value));
+    }
+
+    if let Some(error) = params.get("error") {
+        return Err(match params.get("error_description") {
+            Some(description) => format!("{}: {}", error, description),
+            None => error.clone(),
+        });
+    }
+    if params.get("state").map(String::as_str) != Some(expected_state) {
+        return Err("the authorization response does not match the request state".to_string());
+    }
+    if let Some(code) = params.get("code") {
+        return Ok(Redirect::Code(code.clone()));
+    }
+    Token::from_params(&params, now)
+        .map(Redirect::Token)
+        .ok_or_else(|| "the authorization response has neither a code nor a token".to_string())
+}
+
+fn form_encode(params: &[(&str, String)]) -> String {
+    params
+        .iter()
+        .map(|(name, value)| format!("{}={}", encode_component(name), encode_component(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+pub fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Exchange `grant` for a token at `token_url`.
+pub async fn request_token(token_url: &str, client: &OAuthClient, grant: &Grant<'_>) -> Result<Token, JsValue> {
+    let response = http::execute(&token_request(token_url, client, grant)).await?;
+    parse_token_response(&response, now()).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Run `kind` against `flow` and return the granted token.
+pub async fn authorize(
+    kind: FlowKind,
+    flow: &OAuthFlow,
+    client: &OAuthClient,
+    scopes: &[String],
+    redirect_uri: &str,
+    use_pkce: bool,
+) -> Result<Token, JsValue> {
+    let missing = |field: &str| JsValue::from_str(&format!("the {} flow has no {}", kind.key(), field));
+    match kind {
+        FlowKind::ClientCredentials => {
+            let token_url = flow.token_url.as_deref().ok_or_else(|| missing("tokenUrl"))?;
+            request_token(token_url, client, &Grant::ClientCredentials { scopes }).await
+        }
+        FlowKind::AuthorizationCode | FlowKind::Implicit => {
+            let endpoint = flow.authorization_url.as_deref().ok_or_else(|| missing("authorizationUrl"))?;
+            let state = code_verifier(&random_bytes(16)?);
+            let verifier = match (kind, use_pkce) {
+                (FlowKind::AuthorizationCode, true) => Some(code_verifier(&random_bytes(32)?)),
+                _ => None,
+            };
+            let challenge = verifier.as_deref().map(code_challenge);
+            let url = authorization_url(
+                endpoint,
+                &AuthorizationRequest {
+                    response_type: if kind == FlowKind::Implicit { "token" } else { "code" },
+                    client,
+                    redirect_uri,
+                    scopes,
+                    state: &state,
+                    code_challenge: challenge.as_deref(),
+                },
+            );
+
+            let redirected = authorize_in_popup(&url).await?;
+            match parse_redirect(&redirected, &state, now()).map_err(|e| JsValue::from_str(&e))? {
+                Redirect::Token(token) => Ok(token),
+                Redirect::Code(code) => {
+                    let token_url = flow.token_url.as_deref().ok_or_else(|| missing("tokenUrl"))?;
+                    let grant = Grant::AuthorizationCode {
+                        code: &code,
+                        redirect_uri,
+                        code_verifier: verifier.as_deref(),
+                    };
+                    request_token(token_url, client, &grant).await
+                }
+            }
+        }
+    }
+}
+
+/// The page the authorization server redirects to: `configured`, resolved against the
+/// current page, or else `oauth2-redirect.html` next to it. The redirect page reports back
+/// with `postMessage`, so it has to share the origin of the current page.
+pub fn redirect_url(configured: Option<&str>) -> Result<String, String> {
+    let location = window().ok_or("no global `window` exists")?.location();
+    let js_error = |e: JsValue| e.as_string().unwrap_or_else(|| format!("{:?}", e));
+    let origin = location.origin().map_err(js_error)?;
+    let Some(configured) = configured else {
+        let path = location.pathname().map_err(js_error)?;
+        let directory = &path[..path.rfind('/').map(|i| i + 1).unwrap_or(0)];
+        return Ok(format!("{}{}oauth2-redirect.html", origin, directory));
+    };
+    let url = resolve_url(&location.href().map_err(js_error)?, configured);
+    check_redirect_origin(&origin, &url)?;
+    Ok(url)
+}
+
+fn check_redirect_origin(page_origin: &str, redirect_url: &str) -> Result<(), String> {
+    let origin = url::Url::parse(redirect_url)
+        .map_err(|e| format!("oauth2.redirectUrl {} is not a valid url: {}", redirect_url, e))?
+        .origin()
+        .ascii_serialization();
+    if origin != page_origin {
+        return Err(format!(
+            "oauth2.redirectUrl {} has to be on {}, the origin of this page, for the login window to report back",
+            redirect_url, page_origin
+        ));
+    }
+    Ok(())
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, JsValue> {
+    let mut bytes = vec![0u8; len];
+    window()
+        .ok_or("no global `window` exists")?
+        .crypto()?
+        .get_random_values_with_u8_array(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Open `url` in a popup and wait for the redirect page to post back the url it landed on.
+async fn authorize_in_popup(url: &str) -> Result<String, JsValue> {
+    let window = window().ok_or("no global `window` exists")?;
+    let popup = window
+        .open_with_url_and_target_and_features(url, "redoc-try-it-out-oauth2", "width=600,height=700")?
+        .ok_or_else(|| JsValue::from_str("the authorization popup was blocked"))?;
+    let origin = window.location().origin()?;
+
+    let mut handlers = None;
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let origin = origin.clone();
+        let onmessage = Closure::wrap(Box::new(move |event: MessageEvent| {
+            if event.origin() != origin {
+                return;
+            }
+            let data = event.data();
+            let message_type = js_sys::Reflect::get(&data, &JsValue::from_str("type")).ok();
+            if message_type.and_then(|t| t.as_string()).as_deref() != Some(REDIRECT_MESSAGE) {
+                return;
+            }
+            let url = js_sys::Reflect::get(&data, &JsValue::from_str("url")).unwrap_or(JsValue::UNDEFINED);
+            if let Err(e) = resolve.call1(&JsValue::NULL, &url) {
+                web_sys::console::error_1(&e);
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+
+        let watched: Window = popup.clone();
+        let onclosed = Closure::wrap(Box::new(move || {
+            if watched.closed().unwrap_or(true) {
+                let reason = JsValue::from_str("the authorization window was closed");
+                if let Err(e) = reject.call1(&JsValue::NULL, &reason) {
+                    web_sys::console::error_1(&e);
+                }
+            }
+        }) as Box<dyn FnMut()>);
+
+        handlers = Some((onmessage, onclosed));
+    });
+    let (onmessage, onclosed) = handlers.ok_or("should have registered the popup handlers")?;
+
+    window.add_event_listener_with_callback("message", onmessage.as_ref().unchecked_ref())?;
+    let interval = window.set_interval_with_callback_and_timeout_and_arguments_0(onclosed.as_ref().unchecked_ref(), 500)?;
+
+    let result = JsFuture::from(promise).await;
+
+    window.remove_event_listener_with_callback("message", onmessage.as_ref().unchecked_ref())?;
+    window.clear_interval_with_handle(interval);
+    popup.close()?;
+
+    result?
+        .as_string()
+        .ok_or_else(|| JsValue::from_str("the redirect page did not send its url"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    fn client() -> OAuthClient {
+        OAuthClient {
+            client_id: "docs".to_string(),
+            client_secret: Some("s3cr3t".to_string()),
+        }
+    }
+
+    // RFC 7636 appendix B
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_pkce_challenge() {
+        let random = [
+            116, 24, 223, 180, 151, 153, 224, 37, 79, 250, 96, 125, 216, 173, 187, 186, 22, 212, 37, 77, 105, 214,
+            191, 240, 91, 88, 5, 88, 83, 132, 141, 121,
+        ];
+        let verifier = code_verifier(&random);
+
+        assert_eq!("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk", verifier);
+        assert_eq!("E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM", code_challenge(&verifier));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_authorization_url() {
+        let scopes = vec!["read:pets".to_string(), "write:pets".to_string()];
+        let url = authorization_url(
+            "https://auth.example.com/authorize",
+            &AuthorizationRequest {
+                response_type: "code",
+                client: &client(),
+                redirect_uri: "https://docs.example.com/oauth2-redirect.html",
+                scopes: &scopes,
+                state: "xyz",
+                code_challenge: Some("E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"),
+            },
+        );
+
+        assert_eq!(
+            "https://auth.example.com/authorize?response_type=code&client_id=docs\
+             &redirect_uri=https%3A%2F%2Fdocs.example.com%2Foauth2-redirect.html&state=xyz\
+             &scope=read%3Apets%20write%3Apets\
+             &code_challenge=E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM&code_challenge_method=S256",
+            url
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_token_request() {
+        let request = token_request(
+            "https://auth.example.com/token",
+            &client(),
+            &Grant::AuthorizationCode {
+                code: "abc",
+                redirect_uri: "https://docs.example.com/cb",
+                code_verifier: Some("verifier"),
+            },
+        );
+
+        assert_eq!("POST", request.method);
+        assert_eq!(
+            Some(Body::Text(
+                "grant_type=authorization_code&code=abc&redirect_uri=https%3A%2F%2Fdocs.example.com%2Fcb\
+                 &code_verifier=verifier&client_id=docs&client_secret=s3cr3t"
+                    .to_string()
+            )),
+            request.body
+        );
+
+        let request = token_request(
+            "https://auth.example.com/token",
+            &OAuthClient {
+                client_id: "docs".to_string(),
+                client_secret: None,
+            },
+            &Grant::RefreshToken { refresh_token: "r1" },
+        );
+        assert_eq!(
+            Some(Body::Text("grant_type=refresh_token&refresh_token=r1&client_id=docs".to_string())),
+            request.body
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_parse_token_response() {
+        let response = HttpResponse {
+            status: 200,
+            body: r#"{"access_token":"t1","token_type":"bearer","expires_in":3600,"refresh_token":"r1"}"#.to_string(),
+            ..Default::default()
+        };
+        let token = parse_token_response(&response, 1_000.0).unwrap();
+
+        assert_eq!(
+            Token {
+                access_token: "t1".to_string(),
+                token_type: "bearer".to_string(),
+                refresh_token: Some("r1".to_string()),
+                expires_at: Some(3_601_000.0),
+                scope: None,
+            },
+            token
+        );
+        assert!(!token.is_expired(1_000.0));
+        assert!(token.is_expired(3_580_000.0));
+
+        let response = HttpResponse {
+            status: 400,
+            body: r#"{"error":"invalid_grant","error_description":"code expired"}"#.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(Err("invalid_grant: code expired".to_string()), parse_token_response(&response, 0.0));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_check_redirect_origin() {
+        assert_eq!(Ok(()), check_redirect_origin("https://docs.example.com", "https://docs.example.com/cb.html"));
+        assert_eq!(
+            Err("oauth2.redirectUrl https://login.example.com/cb.html has to be on https://docs.example.com, the origin of this page, for the login window to report back".to_string()),
+            check_redirect_origin("https://docs.example.com", "https://login.example.com/cb.html")
+        );
+        assert!(check_redirect_origin("https://docs.example.com", "not a url").is_err());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_parse_redirect() {
+        assert_eq!(
+            Ok(Redirect::Code("abc".to_string())),
+            parse_redirect("https://docs.example.com/cb?code=abc&state=xyz", "xyz", 0.0)
+        );
+        assert_eq!(
+            Err("access_denied".to_string()),
+            parse_redirect("https://docs.example.com/cb?error=access_denied&state=xyz", "xyz", 0.0)
+        );
+        assert!(parse_redirect("https://docs.example.com/cb?code=abc&state=other", "xyz", 0.0).is_err());
+
+        let redirect = parse_redirect(
+            "https://docs.example.com/cb#access_token=t%201&token_type=Bearer&expires_in=60&state=xyz",
+            "xyz",
+            0.0,
+        );
+        assert_eq!(
+            Ok(Redirect::Token(Token {
+                access_token: "t 1".to_string(),
+                token_type: "Bearer".to_string(),
+                refresh_token: None,
+                expires_at: Some(60_000.0),
+                scope: None,
+            })),
+            redirect
+        );
+    }
+
+    // a data url stands in for the token endpoint
+    #[wasm_bindgen_test]
+    async fn test_request_token_from_stub_endpoint() {
+        let token_url = "data:application/json,%7B%22access_token%22%3A%22t1%22%2C%22expires_in%22%3A60%7D";
+
+        let token = request_token(token_url, &client(), &Grant::ClientCredentials { scopes: &[] })
+            .await
+            .unwrap();
+
+        assert_eq!("t1", token.access_token);
+        assert_eq!("Bearer", token.token_type);
+        assert!(token.expires_at.is_some());
+    }
+}
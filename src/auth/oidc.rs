@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, js_sys, JsFuture};
+use crate::http::{self, HttpRequest};
+use crate::openapi::{resolve_url, OAuthFlow, OAuthFlows};
+
+/// How long an identity provider may take to answer before its scheme is reported as unavailable.
+const DISCOVERY_TIMEOUT_MS: i32 = 10_000;
+
+/// The parts of an OpenID Provider Metadata document used by the Authorize dialog.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Discovery {
+    pub issuer: Option<String>,
+    pub authorization_endpoint: Option<String>,
+    pub token_endpoint: Option<String>,
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+    /// Absent means `["authorization_code", "implicit"]` (OpenID Connect Discovery section 3).
+    pub grant_types_supported: Option<Vec<String>>,
+}
+
+impl Discovery {
+    pub fn parse(json: &str) -> Result<Discovery, String> {
+        serde_json::from_str(json).map_err(|e| format!("invalid discovery document: {}", e))
+    }
+
+    fn supports(&self, grant_type: &str) -> bool {
+        match &self.grant_types_supported {
+            Some(grant_types) => grant_types.iter().any(|supported| supported == grant_type),
+            None => grant_type == "authorization_code" || grant_type == "implicit",
+        }
+    }
+
+    /// The oauth2 flows the provider advertises, as if the spec had declared them.
+    pub fn flows(&self) -> OAuthFlows {
+        let scopes: BTreeMap<String, String> = self
+            .scopes_supported
+            .iter()
+            .map(|scope| (scope.clone(), String::new()))
+            .collect();
+        let flow = |authorization_url: Option<&String>, token_url: Option<&String>| OAuthFlow {
+            authorization_url: authorization_url.cloned(),
+            token_url: token_url.cloned(),
+            refresh_url: None,
+            scopes: scopes.clone(),
+        };
+
+        let authorization = self.authorization_endpoint.as_ref();
+        let token = self.token_endpoint.as_ref();
+        OAuthFlows {
+            authorization_code: (self.supports("authorization_code") && authorization.is_some() && token.is_some())
+                .then(|| flow(authorization, token)),
+            client_credentials: (self.supports("client_credentials") && token.is_some()).then(|| flow(None, token)),
+            implicit: (self.supports("implicit") && authorization.is_some()).then(|| flow(authorization, None)),
+            password: None,
+        }
+    }
+}
+
+/// Fetch the discovery document at `url`, which may be relative to the spec at `spec_url`.
+pub async fn discover(spec_url: &str, url: &str) -> Result<Discovery, JsValue> {
+    let url = resolve_url(spec_url, url);
+    let mut request = HttpRequest::new("GET", &url);
+    request.set_header("Accept", "application/json");
+    let fetch = future_to_promise({
+        let url = url.clone();
+        async move {
+            let response = http::execute(&request).await?;
+            if response.status >= 400 {
+                return Err(JsValue::from_str(&format!(
+                    "{} answered {} {}",
+                    url, response.status, response.status_text
+                )));
+            }
+            Ok(JsValue::from_str(&response.body))
+        }
+    });
+
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no global `window` exists"))?;
+    let mut settle = None;
+    let timeout = js_sys::Promise::new(&mut |_, reject| settle = Some(reject));
+    let reject = settle.ok_or_else(|| JsValue::from_str("should call the promise executor"))?;
+    let message = JsValue::from_str(&format!("{} did not answer within {} ms", url, DISCOVERY_TIMEOUT_MS));
+    let expire = Closure::once_into_js(move || reject.call1(&JsValue::NULL, &message));
+    let timer =
+        window.set_timeout_with_callback_and_timeout_and_arguments_0(expire.unchecked_ref(), DISCOVERY_TIMEOUT_MS)?;
+    let body = JsFuture::from(js_sys::Promise::race(&js_sys::Array::of2(&fetch, &timeout))).await;
+    window.clear_timeout_with_handle(timer);
+    Discovery::parse(&body?.as_string().unwrap_or_default()).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    const DISCOVERY: &str = r###"
+        {
+            "issuer": "https://id.example.com",
+            "authorization_endpoint": "https://id.example.com/authorize",
+            "token_endpoint": "https://id.example.com/token",
+            "jwks_uri": "https://id.example.com/jwks",
+            "scopes_supported": ["openid", "profile"],
+            "grant_types_supported": ["authorization_code", "client_credentials", "refresh_token"]
+        }
+    "###;
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_flows_from_discovery() {
+        let flows = Discovery::parse(DISCOVERY).unwrap().flows();
+        let scopes = BTreeMap::from([
+            ("openid".to_string(), String::new()),
+            ("profile".to_string(), String::new()),
+        ]);
+
+        assert_eq!(
+            OAuthFlows {
+                authorization_code: Some(OAuthFlow {
+                    authorization_url: Some("https://id.example.com/authorize".to_string()),
+                    token_url: Some("https://id.example.com/token".to_string()),
+                    refresh_url: None,
+                    scopes: scopes.clone(),
+                }),
+                client_credentials: Some(OAuthFlow {
+                    authorization_url: None,
+                    token_url: Some("https://id.example.com/token".to_string()),
+                    refresh_url: None,
+                    scopes,
+                }),
+                implicit: None,
+                password: None,
+            },
+            flows
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_default_grant_types() {
+        let discovery = Discovery {
+            authorization_endpoint: Some("https://id.example.com/authorize".to_string()),
+            token_endpoint: Some("https://id.example.com/token".to_string()),
+            ..Default::default()
+        };
+        let flows = discovery.flows();
+
+        assert!(flows.authorization_code.is_some());
+        assert!(flows.implicit.is_some());
+        assert!(flows.client_credentials.is_none());
+    }
+}
@@ -0,0 +1,294 @@
+//! Reading curl command lines back into requests.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use url::Url;
+use super::form::{MULTIPART, URLENCODED};
+use super::http::{encode_component, form_decode, Body, HttpRequest, Part, PartValue};
+
+/// Options that take a value but do not change the request.
+const IGNORED_WITH_VALUE: &[&str] = &[
+    "-o", "--output", "-m", "--max-time", "--connect-timeout", "-x", "--proxy", "--retry", "-w", "--write-out",
+    "--cacert", "--cert", "--key", "-c", "--cookie-jar", "-r", "--range", "--resolve",
+];
+
+/// The request a curl command line sends; the url is split into `url` and `query`.
+pub fn parse_curl(command: &str) -> Result<HttpRequest, String> {
+    let words = split_words(command)?;
+    let mut words = words.iter().map(String::as_str);
+    match words.next() {
+        Some("curl") => {}
+        _ => return Err("The command does not start with curl".to_string()),
+    }
+
+    let mut method = None;
+    let mut url = None;
+    let mut request = HttpRequest::default();
+    let mut data: Vec<String> = Vec::new();
+    let mut parts: Vec<Part> = Vec::new();
+    let mut as_query = false;
+    while let Some(word) = words.next() {
+        // `-XPOST` and `--request=POST` carry their value in the same word
+        let (flag, attached) = match word.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+            _ if word.len() > 2 && word.starts_with('-') && !word.starts_with("--") && takes_value(&word[..2]) => {
+                (&word[..2], Some(&word[2..]))
+            }
+            _ => (word, None),
+        };
+        let mut value = || {
+            attached
+                .or_else(|| words.next())
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+        match flag {
+            "-X" | "--request" => method = Some(value()?.to_uppercase()),
+            "-H" | "--header" => {
+                if let Some((name, header)) = value()?.split_once(':') {
+                    request.headers.push((name.trim().to_string(), header.trim().to_string()));
+                }
+            }
+            "-b" | "--cookie" => request.cookies.extend(value()?.split(';').filter_map(|cookie| {
+                let (name, value) = cookie.split_once('=')?;
+                Some((name.trim().to_string(), value.trim().to_string()))
+            })),
+            "-u" | "--user" => {
+                let credentials = STANDARD.encode(value()?);
+                request.headers.push(("Authorization".to_string(), format!("Basic {}", credentials)));
+            }
+            "-A" | "--user-agent" => request.headers.push(("User-Agent".to_string(), value()?.to_string())),
+            "-e" | "--referer" => request.headers.push(("Referer".to_string(), value()?.to_string())),
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" => data.push(value()?.to_string()),
+            "--data-urlencode" => data.push(url_encode_data(value()?)),
+            "--json" => {
+                data.push(value()?.to_string());
+                request.headers.push(("Content-Type".to_string(), "application/json".to_string()));
+                request.headers.push(("Accept".to_string(), "application/json".to_string()));
+            }
+            "-F" | "--form" | "--form-string" => {
+                let field = value()?;
+                let (name, content) = field.split_once('=').unwrap_or((field, ""));
+                // files can not be read from here, they have to be picked again
+                if flag != "--form-string" && (content.starts_with('@') || content.starts_with('<')) {
+                    continue;
+                }
+                let (text, content_type) = match content.split_once(";type=") {
+                    Some((text, content_type)) if flag != "--form-string" => (text, Some(content_type.to_string())),
+                    _ => (content, None),
+                };
+                parts.push(Part {
+                    name: name.to_string(),
+                    value: PartValue::Text(text.to_string()),
+                    content_type,
+                });
+            }
+            "-G" | "--get" => as_query = true,
+            "-I" | "--head" => method = Some("HEAD".to_string()),
+            "--url" => url = Some(value()?.to_string()),
+            _ if IGNORED_WITH_VALUE.contains(&flag) => {
+                value()?;
+            }
+            _ if flag.starts_with('-') => {}
+            _ => url = Some(word.to_string()),
+        }
+    }
+
+    let url = url.ok_or_else(|| "The command has no url".to_string())?;
+    let mut parsed = Url::parse(&url)
+        .or_else(|_| Url::parse(&format!("http://{}", url)))
+        .map_err(|e| format!("Invalid url {}: {}", url, e))?;
+    request.query = parsed.query().map(form_decode).unwrap_or_default();
+    parsed.set_query(None);
+    parsed.set_fragment(None);
+    request.url = parsed.to_string();
+
+    let data = data.join("&");
+    request.method = method.unwrap_or_else(|| {
+        if (data.is_empty() || as_query) && parts.is_empty() {
+            "GET".to_string()
+        } else {
+            "POST".to_string()
+        }
+    });
+    let content_type = request
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.clone());
+    if !parts.is_empty() {
+        if content_type.is_none() {
+            request.headers.push(("Content-Type".to_string(), MULTIPART.to_string()));
+        }
+        request.body = Some(Body::Multipart(parts));
+    } else if as_query {
+        request.query.extend(form_decode(&data));
+    } else if !data.is_empty() {
+        match content_type {
+            Some(content_type) if !content_type.starts_with(URLENCODED) => request.body = Some(Body::Text(data)),
+            // curl sends data as a form unless told otherwise
+            _ => {
+                if content_type.is_none() {
+                    request.headers.push(("Content-Type".to_string(), URLENCODED.to_string()));
+                }
+                request.body = Some(Body::UrlEncoded(form_decode(&data)));
+            }
+        }
+    }
+    Ok(request)
+}
+
+fn takes_value(flag: &str) -> bool {
+    matches!(flag, "-X" | "-H" | "-b" | "-u" | "-A" | "-e" | "-d" | "-F" | "-o" | "-m" | "-x" | "-w" | "-c" | "-r")
+}
+
+/// `--data-urlencode` content: `name=value` encodes the value, anything else is encoded whole.
+fn url_encode_data(content: &str) -> String {
+    match content.split_once('=') {
+        Some((name, value)) if !name.is_empty() => format!("{}={}", name, encode_component(value)),
+        Some((_, value)) => encode_component(value),
+        None => encode_component(content),
+    }
+}
+
+/// Split a POSIX shell command line into words, honouring quotes, backslash
+/// escapes and line continuations.
+fn split_words(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated ' quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("Unterminated \" quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated \" quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') => {
+                    chars.next_if_eq(&'\n');
+                }
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => {}
+            },
+            c if c.is_whitespace() => {
+                if let Some(word) = word.take() {
+                    words.push(word);
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_split_words() {
+        assert_eq!(
+            vec!["curl", "-H", "X-Name: it's", "a b\\c", "$HOME", "next"],
+            split_words("curl -H 'X-Name: it'\\''s' \"a b\\c\" \"\\$HOME\" \\\n  next").unwrap()
+        );
+        assert!(split_words("curl 'open").is_err());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_json_request() {
+        let request = parse_curl(
+            r#"curl -X PUT 'https://petstore.example.com/v1/pet/5?dry-run=true&tag=a%20b' \
+  -H 'Content-Type: application/json' \
+  -H "Authorization: Bearer t0k3n" \
+  -b 'session=abc; theme=dark' \
+  --compressed -s \
+  --data-raw '{"name": "Rex"}'"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            HttpRequest {
+                method: "PUT".to_string(),
+                url: "https://petstore.example.com/v1/pet/5".to_string(),
+                query: pairs(&[("dry-run", "true"), ("tag", "a b")]),
+                headers: pairs(&[("Content-Type", "application/json"), ("Authorization", "Bearer t0k3n")]),
+                cookies: pairs(&[("session", "abc"), ("theme", "dark")]),
+                body: Some(Body::Text(r#"{"name": "Rex"}"#.to_string())),
+            },
+            request
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_form_requests() {
+        let request = parse_curl("curl https://example.com/login -d user=me --data-urlencode 'note=a&b' -u me:pw").unwrap();
+        assert_eq!("POST", request.method);
+        assert_eq!(
+            pairs(&[("Authorization", "Basic bWU6cHc="), ("Content-Type", URLENCODED)]),
+            request.headers
+        );
+        assert_eq!(Some(Body::UrlEncoded(pairs(&[("user", "me"), ("note", "a&b")]))), request.body);
+
+        let request = parse_curl("curl -XPOST example.com/pet/5/upload -F name=Rex -F 'meta={};type=application/json' -F file=@dog.png").unwrap();
+        assert_eq!("http://example.com/pet/5/upload", request.url);
+        assert_eq!(
+            Some(Body::Multipart(vec![
+                Part {
+                    name: "name".to_string(),
+                    value: PartValue::Text("Rex".to_string()),
+                    content_type: None,
+                },
+                Part {
+                    name: "meta".to_string(),
+                    value: PartValue::Text("{}".to_string()),
+                    content_type: Some("application/json".to_string()),
+                },
+            ])),
+            request.body
+        );
+
+        let request = parse_curl("curl -G https://example.com/pet --data status=sold").unwrap();
+        assert_eq!("GET", request.method);
+        assert_eq!(pairs(&[("status", "sold")]), request.query);
+        assert_eq!(None, request.body);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_invalid_commands() {
+        assert_eq!(Err("The command does not start with curl".to_string()), parse_curl("wget https://example.com"));
+        assert_eq!(Err("The command has no url".to_string()), parse_curl("curl -H 'Accept: */*'"));
+        assert_eq!(Err("-H needs a value".to_string()), parse_curl("curl https://example.com -H"));
+    }
+}
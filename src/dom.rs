@@ -0,0 +1,139 @@
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    js_sys, Blob, BlobPropertyBag, Document, Element, Event, EventTarget, HtmlAnchorElement, HtmlElement, ScrollBehavior,
+    ScrollToOptions, Url,
+};
+
+/// Create an element with an optional class list.
+pub fn create(document: &Document, tag: &str, class_name: &str) -> Result<HtmlElement, JsValue> {
+    let element = document.create_element(tag)?.dyn_into::<HtmlElement>()?;
+    if !class_name.is_empty() {
+        element.set_class_name(class_name);
+    }
+    Ok(element)
+}
+
+/// Create an element holding only text.
+pub fn text(document: &Document, tag: &str, class_name: &str, text: &str) -> Result<HtmlElement, JsValue> {
+    let element = create(document, tag, class_name)?;
+    element.set_text_content(Some(text));
+    Ok(element)
+}
+
+/// Collect the elements matching `selector` below `root`.
+pub fn query_all(root: &Element, selector: &str) -> Result<Vec<Element>, JsValue> {
+    let nodes = root.query_selector_all(selector)?;
+    Ok((0..nodes.length())
+        .filter_map(|i| nodes.item(i))
+        .filter_map(|node| node.dyn_into::<Element>().ok())
+        .collect())
+}
+
+/// Insert `node` right after `sibling`.
+pub fn insert_after(sibling: &Element, node: &Element) -> Result<(), JsValue> {
+    sibling.insert_adjacent_element("afterend", node)?;
+    Ok(())
+}
+
+/// Add every whitespace separated class in `class_names`.
+pub fn add_classes(element: &Element, class_names: &str) -> Result<(), JsValue> {
+    for class_name in class_names.split_whitespace() {
+        element.class_list().add_1(class_name)?;
+    }
+    Ok(())
+}
+
+/// Remove every whitespace separated class in `class_names`.
+pub fn remove_classes(element: &Element, class_names: &str) -> Result<(), JsValue> {
+    for class_name in class_names.split_whitespace() {
+        element.class_list().remove_1(class_name)?;
+    }
+    Ok(())
+}
+
+/// Inject a `<style>` tag once per document, with the CSP `nonce` if there is one.
+pub fn inject_style(document: &Document, id: &str, css: &str, nonce: Option<&str>) -> Result<(), JsValue> {
+    if document.get_element_by_id(id).is_some() {
+        return Ok(());
+    }
+    let style = document.create_element("style")?;
+    style.set_id(id);
+    if let Some(nonce) = nonce {
+        style.set_attribute("nonce", nonce)?;
+    }
+    style.set_text_content(Some(css));
+    document
+        .head()
+        .ok_or_else(|| JsValue::from_str("should have a head"))?
+        .append_child(&style)?;
+    Ok(())
+}
+
+/// Scroll the window smoothly until `element` is `offset` pixels below its top.
+pub fn scroll_to(element: &Element, offset: f64) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no global `window` exists"))?;
+    let top = element.get_bounding_client_rect().top() + window.scroll_y()? - offset;
+    let options = ScrollToOptions::new();
+    options.set_top(top.max(0.0));
+    options.set_behavior(ScrollBehavior::Smooth);
+    window.scroll_to_with_scroll_to_options(&options);
+    Ok(())
+}
+
+/// Let the browser save `text` as a file named `file_name`.
+pub fn download(document: &Document, file_name: &str, mime_type: &str, text: &str) -> Result<(), JsValue> {
+    let options = BlobPropertyBag::new();
+    options.set_type(mime_type);
+    let blob = Blob::new_with_str_sequence_and_options(&js_sys::Array::of1(&JsValue::from_str(text)), &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let link = document.create_element("a")?.dyn_into::<HtmlAnchorElement>()?;
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    // some browsers only start the download after this task, so the url is kept until the next one
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no global `window` exists"))?;
+    let revoke = Closure::once_into_js(move || {
+        if let Err(e) = Url::revoke_object_url(&url) {
+            web_sys::console::error_1(&e);
+        }
+    });
+    window.set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), 1000)?;
+    Ok(())
+}
+
+struct Listener {
+    target: EventTarget,
+    event: String,
+    closure: Closure<dyn FnMut(Event)>,
+}
+
+/// Event listeners that stay registered until `clear` removes them and frees their closures.
+#[derive(Default)]
+pub struct Listeners {
+    listeners: RefCell<Vec<Listener>>,
+}
+
+impl Listeners {
+    pub fn add(&self, target: &EventTarget, event: &str, handler: impl FnMut(Event) + 'static) -> Result<(), JsValue> {
+        let closure = Closure::<dyn FnMut(Event)>::new(handler);
+        target.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())?;
+        self.listeners.borrow_mut().push(Listener {
+            target: target.clone(),
+            event: event.to_string(),
+            closure,
+        });
+        Ok(())
+    }
+
+    /// Remove every listener. Must not be called from one of them.
+    pub fn clear(&self) {
+        for listener in self.listeners.take() {
+            let callback = listener.closure.as_ref().unchecked_ref();
+            if let Err(e) = listener.target.remove_event_listener_with_callback(&listener.event, callback) {
+                web_sys::console::error_1(&e);
+            }
+        }
+    }
+}
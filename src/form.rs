@@ -0,0 +1,318 @@
+use serde_json::Value;
+use web_sys::File;
+use super::http::{Body, Pairs, Part, PartValue};
+use super::openapi::{request_sample, MediaType, SampleOptions, Schema, Spec};
+
+pub const URLENCODED: &str = "application/x-www-form-urlencoded";
+pub const MULTIPART: &str = "multipart/form-data";
+
+/// Whether `media_type` is sent as form fields rather than as text.
+pub fn is_form(media_type: &str) -> bool {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    essence.eq_ignore_ascii_case(URLENCODED) || essence.eq_ignore_ascii_case(MULTIPART)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Text,
+    Boolean,
+    File,
+    /// Objects, edited and sent as JSON.
+    Json,
+}
+
+/// A property of a form request body schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormField {
+    pub name: String,
+    pub kind: FieldKind,
+    /// Arrays take several values and repeat the field once per value.
+    pub multiple: bool,
+    pub required: bool,
+    pub description: Option<String>,
+    /// `enum` values, as they are sent.
+    pub options: Vec<String>,
+    /// Values the field is prefilled with.
+    pub sample: Vec<String>,
+    /// `encoding.contentType` of the part.
+    pub content_type: Option<String>,
+    /// `encoding.explode`; when false the values of an array are sent comma separated.
+    pub explode: bool,
+    /// The dereferenced property schema, for validation.
+    pub schema: Schema,
+}
+
+/// What was entered for a field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Text(Vec<String>),
+    Files(Vec<File>),
+}
+
+/// The fields of a form media type, one per schema property.
+pub fn form_fields(spec: &Spec, media: &MediaType, options: SampleOptions) -> Vec<FormField> {
+    let Some(schema) = &media.schema else {
+        return Vec::new();
+    };
+    let schema = spec.dereference_schema(schema);
+    let properties = flatten_all_of(&schema);
+    let sample = request_sample(spec, &schema, options);
+
+    properties
+        .into_iter()
+        .filter(|(_, property, _)| !property.read_only)
+        .map(|(name, property, required)| {
+            let is_array = property.primary_type() == Some("array");
+            let item = match (&property.items, is_array) {
+                (Some(items), true) => items.as_ref().clone(),
+                _ => property.clone(),
+            };
+            let kind = if is_binary(&item) {
+                FieldKind::File
+            } else {
+                match item.primary_type() {
+                    Some("boolean") => FieldKind::Boolean,
+                    Some("object") => FieldKind::Json,
+                    None if !item.properties.is_empty() => FieldKind::Json,
+                    _ => FieldKind::Text,
+                }
+            };
+            let encoding = media.encoding.get(&name);
+            let sample = match (kind, sample.get(&name)) {
+                (FieldKind::File, _) | (_, None) => Vec::new(),
+                (FieldKind::Json, Some(value)) if !is_array => vec![serde_json::to_string_pretty(value).unwrap_or_default()],
+                (_, Some(Value::Array(values))) => values.iter().map(to_text).collect(),
+                (_, Some(value)) => vec![to_text(value)],
+            };
+            FormField {
+                kind,
+                multiple: is_array,
+                required,
+                description: property.description.clone(),
+                options: item.enumeration.iter().flatten().map(to_text).collect(),
+                sample,
+                content_type: encoding.and_then(|encoding| encoding.content_type.clone()),
+                explode: encoding.and_then(|encoding| encoding.explode).unwrap_or(true),
+                schema: property,
+                name,
+            }
+        })
+        .collect()
+}
+
+/// Properties of `schema` and of its `allOf` members, with whether they are required.
+fn flatten_all_of(schema: &Schema) -> Vec<(String, Schema, bool)> {
+    let mut properties: Vec<(String, Schema, bool)> = Vec::new();
+    for member in schema.all_of.iter().chain(std::iter::once(schema)) {
+        for (name, property) in &member.properties {
+            let required = member.required.contains(name) || schema.required.contains(name);
+            properties.retain(|(existing, _, _)| existing != name);
+            properties.push((name.clone(), property.clone(), required));
+        }
+    }
+    properties
+}
+
+fn is_binary(schema: &Schema) -> bool {
+    schema.primary_type() == Some("string") && matches!(schema.format.as_deref(), Some("binary") | Some("base64"))
+}
+
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The request body for the values entered in `fields`, in the order of `fields`.
+pub fn form_body(media_type: &str, fields: &[FormField], values: &[FieldValue]) -> Body {
+    if media_type.split(';').next().unwrap_or_default().trim().eq_ignore_ascii_case(MULTIPART) {
+        Body::Multipart(multipart(fields, values))
+    } else {
+        Body::UrlEncoded(url_encoded(fields, values))
+    }
+}
+
+fn url_encoded(fields: &[FormField], values: &[FieldValue]) -> Pairs {
+    let mut pairs = Vec::new();
+    for (field, value) in fields.iter().zip(values) {
+        let FieldValue::Text(texts) = value else {
+            continue;
+        };
+        let texts: Vec<&String> = texts.iter().filter(|text| !text.is_empty()).collect();
+        match field.kind {
+            // objects are exploded into their members, as form style does by default
+            FieldKind::Json if field.explode && !field.multiple => {
+                let members = texts
+                    .first()
+                    .and_then(|text| serde_json::from_str::<serde_json::Map<String, Value>>(text).ok())
+                    .unwrap_or_default();
+                pairs.extend(members.iter().map(|(name, value)| (name.clone(), to_text(value))));
+            }
+            _ if field.multiple && !field.explode => {
+                if !texts.is_empty() {
+                    let joined = texts.iter().map(|text| text.as_str()).collect::<Vec<_>>().join(",");
+                    pairs.push((field.name.clone(), joined));
+                }
+            }
+            _ => pairs.extend(texts.into_iter().map(|text| (field.name.clone(), text.clone()))),
+        }
+    }
+    pairs
+}
+
+fn multipart(fields: &[FormField], values: &[FieldValue]) -> Vec<Part> {
+    let mut parts = Vec::new();
+    for (field, value) in fields.iter().zip(values) {
+        let content_type = match (&field.content_type, field.kind) {
+            (Some(content_type), _) if !content_type.starts_with("text/plain") => Some(content_type.clone()),
+            (None, FieldKind::Json) => Some("application/json".to_string()),
+            _ => None,
+        };
+        match value {
+            FieldValue::Text(texts) => parts.extend(texts.iter().filter(|text| !text.is_empty()).map(|text| Part {
+                name: field.name.clone(),
+                value: PartValue::Text(text.clone()),
+                content_type: content_type.clone(),
+            })),
+            FieldValue::Files(files) => parts.extend(files.iter().map(|file| Part {
+                name: field.name.clone(),
+                value: PartValue::File(file.clone()),
+                content_type: None,
+            })),
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn spec() -> Spec {
+        Spec::new(
+            "https://example.com/openapi.json",
+            json!({
+                "openapi": "3.0.3",
+                "paths": {
+                    "/pet/{petId}/upload": {
+                        "post": {
+                            "operationId": "upload",
+                            "requestBody": {
+                                "content": {
+                                    "multipart/form-data": {
+                                        "schema": {
+                                            "type": "object",
+                                            "required": ["file"],
+                                            "properties": {
+                                                "id": { "type": "integer", "readOnly": true },
+                                                "file": { "type": "string", "format": "binary" },
+                                                "thumbnails": { "type": "array", "items": { "type": "string", "format": "binary" } },
+                                                "meta": { "$ref": "#/components/schemas/Meta" },
+                                                "tags": { "type": "array", "items": { "type": "string", "enum": ["a", "b"] } },
+                                                "public": { "type": "boolean" }
+                                            }
+                                        },
+                                        "encoding": {
+                                            "meta": { "contentType": "application/vnd.meta+json" },
+                                            "tags": { "explode": false }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "Meta": { "type": "object", "properties": { "owner": { "type": "string", "example": "me" } } }
+                    }
+                }
+            }),
+        )
+        .unwrap()
+    }
+
+    fn fields() -> Vec<FormField> {
+        let spec = spec();
+        let operation = spec.find_operation_by_id("upload").unwrap();
+        let body = operation.request_body(&spec).unwrap();
+        form_fields(&spec, &body.content[MULTIPART], SampleOptions::default())
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_form_fields() {
+        let fields = fields();
+        let summary: Vec<_> = fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.kind, field.multiple, field.required))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("file", FieldKind::File, false, true),
+                ("thumbnails", FieldKind::File, true, false),
+                ("meta", FieldKind::Json, false, false),
+                ("tags", FieldKind::Text, true, false),
+                ("public", FieldKind::Boolean, false, false),
+            ],
+            summary
+        );
+        assert_eq!(vec!["{\n  \"owner\": \"me\"\n}".to_string()], fields[2].sample);
+        assert_eq!(Some("application/vnd.meta+json".to_string()), fields[2].content_type);
+        assert_eq!(vec!["a".to_string(), "b".to_string()], fields[3].options);
+        assert!(!fields[3].explode);
+        assert_eq!(vec!["true".to_string()], fields[4].sample);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_url_encoded_body() {
+        let fields = fields();
+        let values = vec![
+            FieldValue::Files(Vec::new()),
+            FieldValue::Files(Vec::new()),
+            FieldValue::Text(vec![r#"{ "owner": "me", "age": 3 }"#.to_string()]),
+            FieldValue::Text(vec!["a".to_string(), "b".to_string()]),
+            FieldValue::Text(vec![String::new()]),
+        ];
+
+        assert_eq!(
+            Body::UrlEncoded(vec![
+                ("owner".to_string(), "me".to_string()),
+                ("age".to_string(), "3".to_string()),
+                ("tags".to_string(), "a,b".to_string()),
+            ]),
+            form_body(URLENCODED, &fields, &values)
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_multipart_text_parts() {
+        let fields = fields();
+        let values = vec![
+            FieldValue::Files(Vec::new()),
+            FieldValue::Files(Vec::new()),
+            FieldValue::Text(vec!["{}".to_string()]),
+            FieldValue::Text(vec!["a".to_string(), "b".to_string()]),
+            FieldValue::Text(vec!["false".to_string()]),
+        ];
+        let text = |name: &str, value: &str, content_type: Option<&str>| Part {
+            name: name.to_string(),
+            value: PartValue::Text(value.to_string()),
+            content_type: content_type.map(str::to_string),
+        };
+
+        assert_eq!(
+            Body::Multipart(vec![
+                text("meta", "{}", Some("application/vnd.meta+json")),
+                text("tags", "a", None),
+                text("tags", "b", None),
+                text("public", "false", None),
+            ]),
+            form_body("multipart/form-data; boundary=x", &fields, &values)
+        );
+    }
+}
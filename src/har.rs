@@ -0,0 +1,514 @@
+//! HTTP Archive (HAR 1.2) documents of the requests sent from the consoles.
+//!
+//! See <http://www.softwareishard.com/blog/har-12-spec/>.
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+use super::form::{MULTIPART, URLENCODED};
+use super::history::{HistoryEntry, StoredBody, StoredRequest, StoredResponse};
+use super::http::{find_header, form_decode, Pairs};
+
+const VERSION: &str = "1.2";
+const HTTP_VERSION: &str = "HTTP/1.1";
+/// `-1` stands for a size that is not known.
+const UNKNOWN: i64 = -1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Har {
+    pub log: Log,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Log {
+    pub version: String,
+    pub creator: Creator,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Creator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    pub started_date_time: String,
+    /// Milliseconds between sending the request and receiving the response.
+    pub time: f64,
+    pub request: Request,
+    pub response: Response,
+    #[serde(default)]
+    pub cache: Cache,
+    #[serde(default)]
+    pub timings: Timings,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Request {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<NameValue>,
+    #[serde(default)]
+    pub headers: Vec<NameValue>,
+    #[serde(default)]
+    pub query_string: Vec<NameValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<PostData>,
+    #[serde(default = "unknown")]
+    pub headers_size: i64,
+    #[serde(default = "unknown")]
+    pub body_size: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    pub status: u16,
+    #[serde(default)]
+    pub status_text: String,
+    #[serde(default)]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<NameValue>,
+    #[serde(default)]
+    pub headers: Vec<NameValue>,
+    pub content: Content,
+    #[serde(default, rename = "redirectURL")]
+    pub redirect_url: String,
+    #[serde(default = "unknown")]
+    pub headers_size: i64,
+    #[serde(default = "unknown")]
+    pub body_size: i64,
+    /// Why no response was received, as browsers record failed requests.
+    #[serde(default, rename = "_error", skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NameValue {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PostData {
+    pub mime_type: String,
+    #[serde(default)]
+    pub params: Vec<Param>,
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Param {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Content {
+    pub size: i64,
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Cache {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Timings {
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+}
+
+fn unknown() -> i64 {
+    UNKNOWN
+}
+
+impl Har {
+    pub fn new(entries: &[HistoryEntry]) -> Har {
+        Har {
+            log: Log {
+                version: VERSION.to_string(),
+                creator: Creator {
+                    name: env!("CARGO_PKG_NAME").to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                },
+                entries: entries.iter().map(Entry::from).collect(),
+            },
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub fn parse(text: &str) -> Result<Har, String> {
+        serde_json::from_str(text).map_err(|e| format!("Invalid HAR file: {}", e))
+    }
+}
+
+impl From<&HistoryEntry> for Entry {
+    fn from(entry: &HistoryEntry) -> Entry {
+        let request = &entry.request;
+        let post_data = request.body.as_ref().map(|body| {
+            let content_type = find_header(&request.headers, "content-type").map(str::to_string);
+            match body {
+                StoredBody::Text { text } => PostData {
+                    mime_type: content_type.unwrap_or_default(),
+                    params: Vec::new(),
+                    text: text.clone(),
+                },
+                StoredBody::UrlEncoded { fields } => PostData {
+                    mime_type: content_type.unwrap_or_else(|| URLENCODED.to_string()),
+                    params: fields
+                        .iter()
+                        .map(|(name, value)| Param {
+                            name: name.clone(),
+                            value: Some(value.clone()),
+                            file_name: None,
+                            content_type: None,
+                        })
+                        .collect(),
+                    text: form_urlencoded(fields),
+                },
+                StoredBody::Multipart { parts } => PostData {
+                    mime_type: content_type.unwrap_or_else(|| MULTIPART.to_string()),
+                    params: parts
+                        .iter()
+                        .map(|(name, text, content_type)| Param {
+                            name: name.clone(),
+                            value: Some(text.clone()),
+                            file_name: None,
+                            content_type: content_type.clone(),
+                        })
+                        .collect(),
+                    text: String::new(),
+                },
+            }
+        });
+        let query_string = Url::parse(&request.url)
+            .ok()
+            .and_then(|url| url.query().map(form_decode))
+            .unwrap_or_default();
+        let body_size = post_data.as_ref().map_or(0, |post_data| post_data.text.len() as i64);
+
+        let response = match &entry.response {
+            Some(response) => Response {
+                status: response.status,
+                status_text: response.status_text.clone(),
+                http_version: HTTP_VERSION.to_string(),
+                cookies: Vec::new(),
+                headers: name_values(&response.headers),
+                content: Content {
+                    size: response.body.len() as i64,
+                    mime_type: find_header(&response.headers, "content-type").unwrap_or_default().to_string(),
+                    text: Some(response.body.clone()),
+                },
+                redirect_url: find_header(&response.headers, "location").unwrap_or_default().to_string(),
+                headers_size: UNKNOWN,
+                body_size: response.body.len() as i64,
+                error: None,
+            },
+            None => Response {
+                status: 0,
+                status_text: String::new(),
+                http_version: String::new(),
+                cookies: Vec::new(),
+                headers: Vec::new(),
+                content: Content {
+                    size: 0,
+                    mime_type: String::new(),
+                    text: None,
+                },
+                redirect_url: String::new(),
+                headers_size: UNKNOWN,
+                body_size: UNKNOWN,
+                error: entry.error.clone(),
+            },
+        };
+        let time = entry.response.as_ref().map_or(0.0, |response| response.duration_ms);
+
+        Entry {
+            started_date_time: iso_date_time(entry.sent_at),
+            time,
+            request: Request {
+                method: request.method.clone(),
+                url: request.url.clone(),
+                http_version: HTTP_VERSION.to_string(),
+                cookies: name_values(&request.cookies),
+                headers: name_values(&request.headers),
+                query_string: name_values(&query_string),
+                post_data,
+                headers_size: UNKNOWN,
+                body_size,
+            },
+            response,
+            cache: Cache {},
+            timings: Timings {
+                send: 0.0,
+                wait: time,
+                receive: 0.0,
+            },
+        }
+    }
+}
+
+impl From<&Entry> for HistoryEntry {
+    /// The entry as restored into a console; file parameters are left out.
+    fn from(entry: &Entry) -> HistoryEntry {
+        let request = &entry.request;
+        let body = request.post_data.as_ref().map(|post_data| {
+            let text_params = || {
+                post_data
+                    .params
+                    .iter()
+                    .filter(|param| param.file_name.is_none())
+                    .map(|param| (param.name.clone(), param.value.clone().unwrap_or_default(), param.content_type.clone()))
+            };
+            if post_data.mime_type.starts_with(URLENCODED) {
+                let fields = if post_data.params.is_empty() {
+                    form_decode(&post_data.text)
+                } else {
+                    text_params().map(|(name, value, _)| (name, value)).collect()
+                };
+                StoredBody::UrlEncoded { fields }
+            } else if post_data.mime_type.starts_with(MULTIPART) && !post_data.params.is_empty() {
+                StoredBody::Multipart { parts: text_params().collect() }
+            } else {
+                StoredBody::Text { text: post_data.text.clone() }
+            }
+        });
+        let response = match &entry.response {
+            Response { status: 0, .. } => None,
+            response => Some(StoredResponse {
+                status: response.status,
+                status_text: response.status_text.clone(),
+                headers: pairs(&response.headers),
+                body: response.content.text.clone().unwrap_or_default(),
+                duration_ms: entry.time,
+            }),
+        };
+        HistoryEntry {
+            sent_at: parse_iso_date_time(&entry.started_date_time).unwrap_or_default(),
+            request: StoredRequest {
+                method: request.method.to_uppercase(),
+                url: request.url.clone(),
+                // the cookies are restored from their own list
+                headers: pairs(&request.headers)
+                    .into_iter()
+                    .filter(|(name, _)| !name.eq_ignore_ascii_case("cookie"))
+                    .collect(),
+                cookies: pairs(&request.cookies),
+                body,
+            },
+            error: match &response {
+                Some(_) => None,
+                None => Some(entry.response.error.clone().unwrap_or_else(|| "No response was received".to_string())),
+            },
+            response,
+        }
+    }
+}
+
+fn name_values(pairs: &Pairs) -> Vec<NameValue> {
+    pairs
+        .iter()
+        .map(|(name, value)| NameValue {
+            name: name.clone(),
+            value: value.clone(),
+        })
+        .collect()
+}
+
+fn pairs(name_values: &[NameValue]) -> Pairs {
+    name_values
+        .iter()
+        .map(|pair| (pair.name.clone(), pair.value.clone()))
+        .collect()
+}
+
+fn form_urlencoded(fields: &Pairs) -> String {
+    url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(fields)
+        .finish()
+}
+
+/// `2024-01-28T16:21:33.000Z` for milliseconds since the epoch.
+fn iso_date_time(millis: f64) -> String {
+    let millis = millis.max(0.0) as i64;
+    let (days, millis_of_day) = (millis.div_euclid(86_400_000), millis.rem_euclid(86_400_000));
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        millis_of_day / 3_600_000,
+        millis_of_day / 60_000 % 60,
+        millis_of_day / 1000 % 60,
+        millis_of_day % 1000
+    )
+}
+
+/// Milliseconds since the epoch of an ISO 8601 date and time with an offset.
+fn parse_iso_date_time(text: &str) -> Option<f64> {
+    let number = |range: std::ops::Range<usize>| text.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hours, minutes, seconds) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    let rest = text.get(19..)?;
+    let (fraction, offset) = match rest.strip_prefix('.') {
+        Some(rest) => {
+            let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let fraction: f64 = format!("0.{}", &rest[..digits]).parse().ok()?;
+            (fraction, &rest[digits..])
+        }
+        None => (0.0, rest),
+    };
+    let offset_minutes = match offset {
+        "Z" | "" => 0,
+        _ => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (offset_hours, offset_minutes) = offset.get(1..)?.split_once(':')?;
+            sign * (offset_hours.parse::<i64>().ok()? * 60 + offset_minutes.parse::<i64>().ok()?)
+        }
+    };
+    let seconds = days_from_civil(year, month, day) * 86_400 + hours * 3600 + (minutes - offset_minutes) * 60 + seconds;
+    Some(seconds as f64 * 1000.0 + (fraction * 1000.0).round())
+}
+
+// Howard Hinnant's algorithms, http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+    use super::super::http::{Body, HttpRequest, HttpResponse};
+
+    fn entry() -> HistoryEntry {
+        let mut request = HttpRequest::new("POST", "https://example.com/pet");
+        request.query = vec![("dry-run".to_string(), "true".to_string())];
+        request.headers = vec![("Content-Type".to_string(), URLENCODED.to_string())];
+        request.cookies = vec![("session".to_string(), "abc".to_string())];
+        request.body = Some(Body::UrlEncoded(vec![("name".to_string(), "Rex Jr".to_string())]));
+        let response = HttpResponse {
+            status: 201,
+            status_text: "Created".to_string(),
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: "{\"id\":1}".to_string(),
+            duration_ms: 42.0,
+        };
+        HistoryEntry::new(&request, Ok(&response), 1_706_458_893_250.0)
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_har_document() {
+        let har = Har::new(&[entry()]);
+        let json: serde_json::Value = serde_json::from_str(&har.to_json()).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "startedDateTime": "2024-01-28T16:21:33.250Z",
+                "time": 42.0,
+                "request": {
+                    "method": "POST",
+                    "url": "https://example.com/pet?dry-run=true",
+                    "httpVersion": "HTTP/1.1",
+                    "cookies": [{ "name": "session", "value": "abc" }],
+                    "headers": [{ "name": "Content-Type", "value": URLENCODED }],
+                    "queryString": [{ "name": "dry-run", "value": "true" }],
+                    "postData": {
+                        "mimeType": URLENCODED,
+                        "params": [{ "name": "name", "value": "Rex Jr" }],
+                        "text": "name=Rex+Jr"
+                    },
+                    "headersSize": -1,
+                    "bodySize": 11
+                },
+                "response": {
+                    "status": 201,
+                    "statusText": "Created",
+                    "httpVersion": "HTTP/1.1",
+                    "cookies": [],
+                    "headers": [{ "name": "content-type", "value": "application/json" }],
+                    "content": { "size": 8, "mimeType": "application/json", "text": "{\"id\":1}" },
+                    "redirectURL": "",
+                    "headersSize": -1,
+                    "bodySize": 8
+                },
+                "cache": {},
+                "timings": { "send": 0.0, "wait": 42.0, "receive": 0.0 }
+            }),
+            json["log"]["entries"][0]
+        );
+        assert_eq!("1.2", json["log"]["version"]);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_har_round_trip() {
+        let mut failed = entry();
+        failed.response = None;
+        failed.error = Some("Failed to fetch".to_string());
+        let entries = vec![entry(), failed];
+
+        let har = Har::parse(&Har::new(&entries).to_json()).unwrap();
+        assert_eq!(entries, har.log.entries.iter().map(HistoryEntry::from).collect::<Vec<_>>());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_browser_har() {
+        // as saved by the network panel of a browser, with fields this crate does not write
+        let har = Har::parse(
+            r#"{"log": {"version": "1.2", "creator": {"name": "WebInspector", "version": "537.36"}, "pages": [],
+                "entries": [{"startedDateTime": "2024-01-28T17:21:33.5+01:00", "time": 10.5, "_priority": "High",
+                    "request": {"method": "get", "url": "https://example.com/pet/1", "headers": [{"name": "Cookie", "value": "a=b"}]},
+                    "response": {"status": 200, "content": {"size": 2, "mimeType": "application/json", "text": "{}"}}}]}}"#,
+        )
+        .unwrap();
+        let entry = HistoryEntry::from(&har.log.entries[0]);
+        assert_eq!(1_706_458_893_500.0, entry.sent_at);
+        assert_eq!("GET", entry.request.method);
+        assert!(entry.request.headers.is_empty());
+        assert_eq!(Some("{}".to_string()), entry.response.map(|response| response.body));
+
+        assert!(Har::parse("{}").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+//! Requests sent from the consoles and their responses, kept per operation.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use web_sys::Storage;
+use super::http::{form_decode, Body, HttpRequest, HttpResponse, Pairs, Part, PartValue};
+use super::options::HistoryOptions;
+use super::snippets::{mask_secrets, SECRET_PLACEHOLDER};
+
+const STORAGE_PREFIX: &str = "redoc-try-it-out-history:";
+/// Longer response bodies are cut, storages hold a few megabytes at most.
+const MAX_BODY_LENGTH: usize = 64 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// Milliseconds since the epoch.
+    pub sent_at: f64,
+    pub request: StoredRequest,
+    pub response: Option<StoredResponse>,
+    /// Why no response was received.
+    pub error: Option<String>,
+}
+
+/// A request as kept in the history; file parts are left out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredRequest {
+    pub method: String,
+    /// The url including the query string.
+    pub url: String,
+    pub headers: Pairs,
+    pub cookies: Pairs,
+    pub body: Option<StoredBody>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StoredBody {
+    Text { text: String },
+    UrlEncoded { fields: Pairs },
+    /// The text parts, with their content types.
+    Multipart { parts: Vec<(String, String, Option<String>)> },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Pairs,
+    pub body: String,
+    pub duration_ms: f64,
+}
+
+impl HistoryEntry {
+    /// The entry of `request`, with `secrets` masked when `redact` is set.
+    pub fn redacted(
+        request: &HttpRequest,
+        outcome: Result<&HttpResponse, String>,
+        secrets: &[String],
+        redact: bool,
+        sent_at: f64,
+    ) -> HistoryEntry {
+        if redact {
+            HistoryEntry::new(&mask_secrets(request, secrets), outcome, sent_at)
+        } else {
+            HistoryEntry::new(request, outcome, sent_at)
+        }
+    }
+
+    pub fn new(request: &HttpRequest, outcome: Result<&HttpResponse, String>, sent_at: f64) -> HistoryEntry {
+        let body = request.body.as_ref().map(|body| match body {
+            Body::Text(text) => StoredBody::Text { text: text.clone() },
+            Body::UrlEncoded(fields) => StoredBody::UrlEncoded { fields: fields.clone() },
+            Body::Multipart(parts) => StoredBody::Multipart {
+                parts: parts
+                    .iter()
+                    .filter_map(|part| match &part.value {
+                        PartValue::Text(text) => Some((part.name.clone(), text.clone(), part.content_type.clone())),
+                        PartValue::File(_) => None,
+                    })
+                    .collect(),
+            },
+        });
+        let (response, error) = match outcome {
+            Ok(response) => (Some(StoredResponse::from(response)), None),
+            Err(error) => (None, Some(error)),
+        };
+        HistoryEntry {
+            sent_at,
+            request: StoredRequest {
+                method: request.method.clone(),
+                url: request.full_url(),
+                headers: request.headers.clone(),
+                cookies: request.cookies.clone(),
+                body,
+            },
+            response,
+            error,
+        }
+    }
+}
+
+impl StoredRequest {
+    /// The request to restore into a console, with the query split from the url and
+    /// without the values that were redacted.
+    pub fn to_request(&self) -> HttpRequest {
+        let kept = |pairs: &Pairs| -> Pairs {
+            pairs
+                .iter()
+                .filter(|(_, value)| !value.contains(SECRET_PLACEHOLDER))
+                .cloned()
+                .collect()
+        };
+        let (url, query) = match Url::parse(&self.url) {
+            Ok(mut url) => {
+                let query = url.query().map(form_decode).unwrap_or_default();
+                url.set_query(None);
+                (url.to_string(), query)
+            }
+            Err(_) => (self.url.clone(), Vec::new()),
+        };
+        let body = self.body.as_ref().map(|body| match body {
+            StoredBody::Text { text } => Body::Text(text.clone()),
+            StoredBody::UrlEncoded { fields } => Body::UrlEncoded(fields.clone()),
+            StoredBody::Multipart { parts } => Body::Multipart(
+                parts
+                    .iter()
+                    .map(|(name, text, content_type)| Part {
+                        name: name.clone(),
+                        value: PartValue::Text(text.clone()),
+                        content_type: content_type.clone(),
+                    })
+                    .collect(),
+            ),
+        });
+        HttpRequest {
+            method: self.method.clone(),
+            url,
+            query: kept(&query),
+            headers: kept(&self.headers),
+            cookies: kept(&self.cookies),
+            body,
+        }
+    }
+}
+
+impl From<&HttpResponse> for StoredResponse {
+    fn from(response: &HttpResponse) -> StoredResponse {
+        let mut body = response.body.clone();
+        if body.len() > MAX_BODY_LENGTH {
+            let mut end = MAX_BODY_LENGTH;
+            while !body.is_char_boundary(end) {
+                end -= 1;
+            }
+            body.truncate(end);
+        }
+        StoredResponse {
+            status: response.status,
+            status_text: response.status_text.clone(),
+            headers: response.headers.clone(),
+            body,
+            duration_ms: response.duration_ms,
+        }
+    }
+}
+
+impl From<&StoredResponse> for HttpResponse {
+    fn from(response: &StoredResponse) -> HttpResponse {
+        HttpResponse {
+            status: response.status,
+            status_text: response.status_text.clone(),
+            headers: response.headers.clone(),
+            body: response.body.clone(),
+            duration_ms: response.duration_ms,
+        }
+    }
+}
+
+/// The history of every operation, newest entries first.
+#[derive(Debug)]
+pub struct History {
+    /// Where entries are saved, or `None` to keep them in `memory`.
+    storage: Option<Storage>,
+    memory: RefCell<BTreeMap<String, Vec<HistoryEntry>>>,
+    limit: usize,
+}
+
+impl History {
+    /// The history configured by `options`, or `None` when it is disabled.
+    pub fn new(options: &HistoryOptions) -> Option<History> {
+        if !options.enabled || options.limit == 0 {
+            return None;
+        }
+        let window = web_sys::window();
+        let storage = match options.storage.as_str() {
+            "localStorage" => window.and_then(|window| window.local_storage().ok().flatten()),
+            "sessionStorage" => window.and_then(|window| window.session_storage().ok().flatten()),
+            _ => None,
+        };
+        Some(History {
+            storage,
+            memory: RefCell::default(),
+            limit: options.limit as usize,
+        })
+    }
+
+    pub fn entries(&self, operation: &str) -> Vec<HistoryEntry> {
+        match &self.storage {
+            Some(storage) => storage
+                .get_item(&storage_key(operation))
+                .ok()
+                .flatten()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            None => self.memory.borrow().get(operation).cloned().unwrap_or_default(),
+        }
+    }
+
+    pub fn record(&self, operation: &str, entry: HistoryEntry) -> Result<(), String> {
+        let mut entries = self.entries(operation);
+        entries.insert(0, entry);
+        entries.truncate(self.limit);
+        self.save(operation, entries)
+    }
+
+    pub fn clear(&self, operation: &str) -> Result<(), String> {
+        self.save(operation, Vec::new())
+    }
+
+    fn save(&self, operation: &str, entries: Vec<HistoryEntry>) -> Result<(), String> {
+        let Some(storage) = &self.storage else {
+            self.memory.borrow_mut().insert(operation.to_string(), entries);
+            return Ok(());
+        };
+        let key = storage_key(operation);
+        let saved = if entries.is_empty() {
+            storage.remove_item(&key)
+        } else {
+            let json = serde_json::to_string(&entries).map_err(|e| e.to_string())?;
+            storage.set_item(&key, &json)
+        };
+        saved.map_err(|e| format!("Could not save the history: {:?}", e))
+    }
+}
+
+fn storage_key(operation: &str) -> String {
+    format!("{}{}", STORAGE_PREFIX, operation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    fn history(limit: usize) -> History {
+        History {
+            storage: None,
+            memory: RefCell::default(),
+            limit,
+        }
+    }
+
+    fn request(name: &str) -> HttpRequest {
+        let mut request = HttpRequest::new("POST", &format!("https://example.com/pet?name={}", name));
+        request.query = vec![("api_key".to_string(), "k3y".to_string())];
+        request.headers = vec![
+            ("Content-Type".to_string(), "application/json".to_string()),
+            ("Authorization".to_string(), "Bearer t0k3n".to_string()),
+        ];
+        request.body = Some(Body::Text("{}".to_string()));
+        request
+    }
+
+    fn entry(request: &HttpRequest, outcome: Result<&HttpResponse, String>, sent_at: f64) -> HistoryEntry {
+        let secrets = ["t0k3n".to_string(), "k3y".to_string()];
+        HistoryEntry::redacted(request, outcome, &secrets, true, sent_at)
+    }
+
+    fn response() -> HttpResponse {
+        HttpResponse {
+            status: 201,
+            status_text: "Created".to_string(),
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: "{\"id\":1}".to_string(),
+            duration_ms: 12.0,
+        }
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_newest_entries_are_kept() {
+        let history = history(2);
+        for (index, name) in ["a", "b", "c"].iter().enumerate() {
+            history
+                .record("post /pet", entry(&request(name), Ok(&response()), index as f64))
+                .unwrap();
+        }
+        history
+            .record("get /pet", entry(&request("d"), Err("offline".to_string()), 3.0))
+            .unwrap();
+
+        let entries = history.entries("post /pet");
+        assert_eq!(vec![2.0, 1.0], entries.iter().map(|entry| entry.sent_at).collect::<Vec<_>>());
+        assert_eq!("https://example.com/pet?name=c&api_key=%3Csecret%3E", entries[0].request.url);
+        assert_eq!(
+            ("Authorization".to_string(), "Bearer <secret>".to_string()),
+            entries[0].request.headers[1]
+        );
+        assert_eq!(Some(201), entries[0].response.as_ref().map(|response| response.status));
+        assert_eq!(Some("offline".to_string()), history.entries("get /pet")[0].error);
+
+        history.clear("post /pet").unwrap();
+        assert!(history.entries("post /pet").is_empty());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_restored_request() {
+        let history = history(5);
+        history.record("post /pet", entry(&request("Rex"), Ok(&response()), 0.0)).unwrap();
+        let entry = &history.entries("post /pet")[0];
+
+        // what was redacted is left for the Authorize dialog to fill in again
+        let mut expected = HttpRequest::new("POST", "https://example.com/pet");
+        expected.query = vec![("name".to_string(), "Rex".to_string())];
+        expected.headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+        expected.body = Some(Body::Text("{}".to_string()));
+        assert_eq!(expected, entry.request.to_request());
+        assert_eq!(response(), HttpResponse::from(entry.response.as_ref().unwrap()));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_entries_survive_serialization() {
+        let mut request = request("a");
+        request.body = Some(Body::UrlEncoded(vec![("name".to_string(), "Rex".to_string())]));
+        let entry = HistoryEntry::new(&request, Ok(&response()), 1.0);
+
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.contains(r#""body":{"type":"urlEncoded","fields":[["name","Rex"]]}"#));
+        assert_eq!(entry, serde_json::from_str::<HistoryEntry>(&json).unwrap());
+    }
+}
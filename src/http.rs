@@ -0,0 +1,459 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys, Blob, BlobPropertyBag, Document, File, FormData, Headers, HtmlDocument, Request, RequestInit,
+    Response, UrlSearchParams,
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch_with_request(request: &Request) -> js_sys::Promise;
+
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/// Ordered name/value pairs, as used for headers, query and cookies.
+pub type Pairs = Vec<(String, String)>;
+
+/// A request composed in the try-it-out console.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub query: Pairs,
+    pub headers: Pairs,
+    pub cookies: Pairs,
+    pub body: Option<Body>,
+}
+
+/// What a request sends.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Body {
+    Text(String),
+    /// `application/x-www-form-urlencoded` fields.
+    UrlEncoded(Pairs),
+    /// `multipart/form-data` parts; the browser picks the boundary.
+    Multipart(Vec<Part>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub name: String,
+    pub value: PartValue,
+    /// Sent for text parts that are not `text/plain`.
+    pub content_type: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartValue {
+    Text(String),
+    File(File),
+}
+
+impl HttpRequest {
+    pub fn new(method: &str, url: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.to_uppercase(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The target url including the encoded query string.
+    pub fn full_url(&self) -> String {
+        if self.query.is_empty() {
+            return self.url.clone();
+        }
+        let query = self
+            .query
+            .iter()
+            .map(|(name, value)| format!("{}={}", encode_component(name), encode_component(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let separator = if self.url.contains('?') { '&' } else { '?' };
+        format!("{}{}{}", self.url, separator, query)
+    }
+
+    /// Replace a header, ignoring the case of its name.
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+}
+
+/// The outcome of a try-it-out request.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Pairs,
+    pub body: String,
+    pub duration_ms: f64,
+}
+
+impl HttpResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("content-type")
+    }
+
+    /// The body, re-indented when it is JSON.
+    pub fn pretty_body(&self) -> String {
+        let is_json = self
+            .content_type()
+            .map(|content_type| content_type.contains("json"))
+            .unwrap_or(false);
+        if is_json {
+            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&self.body) {
+                if let Ok(pretty) = serde_json::to_string_pretty(&value) {
+                    return pretty;
+                }
+            }
+        }
+        self.body.clone()
+    }
+}
+
+pub fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Percent-encode everything but the RFC 3986 unreserved characters.
+pub fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Decode a percent-encoded form component, treating `+` as a space.
+pub fn decode_component(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = value
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                i += 2;
+            }
+            (b'+', None) => decoded.push(b' '),
+            (byte, None) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Decode `name=value&…` form or query text.
+pub fn form_decode(text: &str) -> Pairs {
+    text.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode_component(name), decode_component(value))
+        })
+        .collect()
+}
+
+/// Cookies `apply_cookies` replaced, by encoded name, with the value they had before.
+pub type CookieSnapshot = Vec<(String, Option<String>)>;
+
+/// Browsers refuse a `Cookie` request header, so cookies are written to the
+/// document, with their values percent-encoded, and put back by `restore_cookies`
+/// once the request is done. That only works for the origin of the page; for other
+/// origins the cookies are left out with a warning.
+pub fn apply_cookies(document: &Document, request: &HttpRequest) -> Result<CookieSnapshot, JsValue> {
+    if request.cookies.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !same_origin(&document.url()?, &request.url) {
+        web_sys::console::warn_1(&JsValue::from_str(&format!(
+            "Cookie parameters are not sent to {}, browsers only send cookies of the page's origin",
+            request.url
+        )));
+        return Ok(Vec::new());
+    }
+    let document = html_document(document)?;
+    let mut snapshot = Vec::new();
+    for (name, value) in &request.cookies {
+        let name = encode_component(name);
+        if !snapshot.iter().any(|(saved, _)| *saved == name) {
+            let previous = cookie_value(&document.cookie()?, &name);
+            snapshot.push((name.clone(), previous));
+        }
+        document.set_cookie(&format!("{}={}; path=/", name, encode_component(value)))?;
+    }
+    Ok(snapshot)
+}
+
+/// Put back the cookies `apply_cookies` replaced, and remove the ones it added. A cookie
+/// that was there before keeps its value but becomes a session cookie.
+pub fn restore_cookies(document: &Document, snapshot: &CookieSnapshot) -> Result<(), JsValue> {
+    if snapshot.is_empty() {
+        return Ok(());
+    }
+    let document = html_document(document)?;
+    for (name, previous) in snapshot {
+        match previous {
+            Some(value) => document.set_cookie(&format!("{}={}; path=/", name, value))?,
+            None => document.set_cookie(&format!("{}=; path=/; max-age=0", name))?,
+        }
+    }
+    Ok(())
+}
+
+fn html_document(document: &Document) -> Result<&HtmlDocument, JsValue> {
+    document
+        .dyn_ref::<HtmlDocument>()
+        .ok_or_else(|| JsValue::from_str("cookies need an html document"))
+}
+
+/// Whether `request_url` has the origin of `page_url`, and so can be sent cookies.
+fn same_origin(page_url: &str, request_url: &str) -> bool {
+    let Ok(page) = url::Url::parse(page_url) else {
+        return false;
+    };
+    page.join(request_url)
+        .is_ok_and(|request| request.origin() == page.origin() && request.origin().is_tuple())
+}
+
+/// The value of the cookie `name` in a `document.cookie` string.
+fn cookie_value(cookies: &str, name: &str) -> Option<String> {
+    cookies
+        .split(';')
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(cookie_name, _)| *cookie_name == name)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Send `request` with fetch and collect the full response.
+pub async fn execute(request: &HttpRequest) -> Result<HttpResponse, JsValue> {
+    let headers = Headers::new()?;
+    for (name, value) in &request.headers {
+        // the multipart boundary is only known to the browser
+        if matches!(request.body, Some(Body::Multipart(_))) && name.eq_ignore_ascii_case("content-type") {
+            continue;
+        }
+        headers.append(name, value)?;
+    }
+
+    let init = RequestInit::new();
+    init.set_method(&request.method);
+    init.set_headers(&headers);
+    if let Some(body) = &request.body {
+        init.set_body(&body_value(body)?);
+    }
+
+    let fetch_request = Request::new_with_str_and_init(&request.full_url(), &init)?;
+
+    let started = performance_now();
+    let response: Response = JsFuture::from(fetch_with_request(&fetch_request)).await?.dyn_into()?;
+    let body = JsFuture::from(response.text()?).await?.as_string().unwrap_or_default();
+    let duration_ms = performance_now() - started;
+
+    Ok(HttpResponse {
+        status: response.status(),
+        status_text: response.status_text(),
+        headers: collect_headers(&response.headers())?,
+        body,
+        duration_ms,
+    })
+}
+
+fn body_value(body: &Body) -> Result<JsValue, JsValue> {
+    Ok(match body {
+        Body::Text(text) => JsValue::from_str(text),
+        Body::UrlEncoded(fields) => {
+            let params = UrlSearchParams::new()?;
+            for (name, value) in fields {
+                params.append(name, value);
+            }
+            params.into()
+        }
+        Body::Multipart(parts) => {
+            let form = FormData::new()?;
+            for part in parts {
+                match (&part.value, &part.content_type) {
+                    (PartValue::File(file), _) => form.append_with_blob_and_filename(&part.name, file, &file.name())?,
+                    (PartValue::Text(text), Some(content_type)) => {
+                        let options = BlobPropertyBag::new();
+                        options.set_type(content_type);
+                        let blob = Blob::new_with_str_sequence_and_options(
+                            &js_sys::Array::of1(&JsValue::from_str(text)),
+                            &options,
+                        )?;
+                        form.append_with_blob(&part.name, &blob)?;
+                    }
+                    (PartValue::Text(text), None) => form.append_with_str(&part.name, text)?,
+                }
+            }
+            form.into()
+        }
+    })
+}
+
+fn collect_headers(headers: &Headers) -> Result<Pairs, JsValue> {
+    let mut collected = Vec::new();
+    if let Some(entries) = js_sys::try_iter(headers)? {
+        for entry in entries {
+            let entry: js_sys::Array = entry?.dyn_into()?;
+            collected.push((
+                entry.get(0).as_string().unwrap_or_default(),
+                entry.get(1).as_string().unwrap_or_default(),
+            ));
+        }
+    }
+    Ok(collected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    // A stand-in server: fetches to http://stand-in.test echo the request they got as JSON,
+    // everything else goes to the real fetch.
+    #[wasm_bindgen(inline_js = r#"
+        export function install_stand_in() {
+            if (globalThis.standInFetch) return;
+            const fetch = globalThis.fetch;
+            globalThis.standInFetch = async (request) => {
+                if (new URL(request.url).host !== "stand-in.test") return fetch(request);
+                const echo = {
+                    method: request.method,
+                    url: request.url,
+                    contentType: request.headers.get("content-type"),
+                    apiKey: request.headers.get("x-api-key"),
+                    body: await request.text(),
+                };
+                return new Response(JSON.stringify(echo), {
+                    status: 201,
+                    statusText: "Created",
+                    headers: { "Content-Type": "application/json", "X-Request-Id": "42" },
+                });
+            };
+            globalThis.fetch = globalThis.standInFetch;
+        }
+    "#)]
+    extern "C" {
+        fn install_stand_in();
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_full_url_encodes_query() {
+        let mut request = HttpRequest::new("get", "https://petstore.swagger.io/v2/pet/findByStatus");
+        request.query.push(("status".to_string(), "sold out".to_string()));
+        request.query.push(("tag".to_string(), "a&b".to_string()));
+
+        assert_eq!(
+            "https://petstore.swagger.io/v2/pet/findByStatus?status=sold%20out&tag=a%26b",
+            request.full_url()
+        );
+        assert_eq!("GET", request.method);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_pretty_body() {
+        let response = HttpResponse {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: r#"{"id":1}"#.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!("{\n  \"id\": 1\n}", response.pretty_body());
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_execute() {
+        install_stand_in();
+        let mut request = HttpRequest::new("post", "http://stand-in.test/pet");
+        request.query.push(("dry run".to_string(), "yes".to_string()));
+        request.set_header("X-Api-Key", "secret");
+        request.set_header("Content-Type", "application/json");
+        request.body = Some(Body::Text(r#"{"name":"Rex"}"#.to_string()));
+
+        let response = execute(&request).await.unwrap();
+
+        assert_eq!(201, response.status);
+        assert_eq!("Created", response.status_text);
+        assert_eq!(Some("application/json"), response.content_type());
+        assert!(response.headers.contains(&("x-request-id".to_string(), "42".to_string())));
+        let echo: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "method": "POST",
+                "url": "http://stand-in.test/pet?dry%20run=yes",
+                "contentType": "application/json",
+                "apiKey": "secret",
+                "body": r#"{"name":"Rex"}"#,
+            }),
+            echo
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_same_origin() {
+        let page = "https://docs.example.com/api/index.html";
+
+        assert!(same_origin(page, "https://docs.example.com/v1/pet"));
+        assert!(same_origin(page, "v1/pet"));
+        assert!(!same_origin(page, "https://api.example.com/v1/pet"));
+        assert!(!same_origin(page, "http://docs.example.com/v1/pet"));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_cookie_value() {
+        let cookies = "theme=dark; session=a%3Bb; empty=";
+
+        assert_eq!(Some("a%3Bb".to_string()), cookie_value(cookies, "session"));
+        assert_eq!(Some(String::new()), cookie_value(cookies, "empty"));
+        assert_eq!(None, cookie_value(cookies, "sess"));
+        assert_eq!(None, cookie_value("", "session"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_body_values() {
+        let fields = Body::UrlEncoded(vec![
+            ("name".to_string(), "Rex Jr".to_string()),
+            ("tags".to_string(), "a&b".to_string()),
+        ]);
+        let params: UrlSearchParams = body_value(&fields).unwrap().dyn_into().unwrap();
+        assert_eq!("name=Rex+Jr&tags=a%26b", String::from(params.to_string()));
+
+        let parts = Body::Multipart(vec![
+            Part {
+                name: "name".to_string(),
+                value: PartValue::Text("Rex".to_string()),
+                content_type: None,
+            },
+            Part {
+                name: "meta".to_string(),
+                value: PartValue::Text("{}".to_string()),
+                content_type: Some("application/json".to_string()),
+            },
+        ]);
+        let form: FormData = body_value(&parts).unwrap().dyn_into().unwrap();
+        assert_eq!(Some("Rex".to_string()), form.get("name").as_string());
+        let meta: Blob = form.get("meta").dyn_into().unwrap();
+        assert_eq!("application/json", meta.type_());
+    }
+}
@@ -1,11 +1,29 @@
 mod theme;
 mod options;
+mod auth;
+mod curl;
+mod openapi;
+mod dom;
+mod form;
+mod har;
+mod history;
+mod http;
+mod loader;
+mod params;
+mod snippets;
+mod try_it_out;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{js_sys, window, Document, Element, HtmlScriptElement};
+use web_sys::{js_sys, window, Document, Element};
 use options::RedocTryItOutOptions;
 use options::RedocOptions;
+use try_it_out::TryItOut;
+use auth::{Auth, AuthDialog};
+use openapi::Spec;
+use serde::Serialize;
 
 #[wasm_bindgen]
 extern "C" {
@@ -13,12 +31,54 @@ extern "C" {
     fn log(s: &str);
 
     #[wasm_bindgen(js_name = init, js_namespace = Redoc)]
-    fn initRedoc(docUrl: String, options: JsValue, element: Element, callback: &js_sys::Function);
+    fn initRedoc(spec: JsValue, options: JsValue, element: Element, callback: &js_sys::Function);
 }
 
+/// Class of the element Redoc renders into, created below the container on every render
+/// so that a later render starts from a fresh one.
+const REDOC_ROOT_CLASS: &str = "redoc-try-it-out-root";
+
 #[wasm_bindgen]
 pub struct RedocTryItOut {
     document: Document,
+    mounted: RefCell<Option<Mounted>>,
+    /// Counts renders and destroys, so that a render superseded while it waited mounts nothing.
+    generation: Cell<u64>,
+}
+
+/// What the last render put on the page, with what it was rendered from.
+struct Mounted {
+    doc_url: String,
+    /// The spec as it was given, before its references were bundled.
+    spec: serde_json::Value,
+    raw_config: JsValue,
+    /// The container given to `init`, if any, rather than the one found by `containerId`.
+    element: Option<Element>,
+    root: Element,
+    auth_dialog: Option<Rc<AuthDialog>>,
+    try_it_out: Option<Rc<TryItOut>>,
+}
+
+impl Mounted {
+    fn unmount(&self) -> Result<(), JsValue> {
+        if let Some(try_it_out) = &self.try_it_out {
+            try_it_out.unmount(&self.root)?;
+        }
+        if let Some(auth_dialog) = &self.auth_dialog {
+            auth_dialog.unmount();
+        }
+        // bundles that export destroy unmount their React tree, with its window listeners;
+        // others are left to the garbage collector once their root is gone
+        let redoc = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("Redoc"))?;
+        if redoc.is_object() {
+            let destroy = js_sys::Reflect::get(&redoc, &JsValue::from_str("destroy"))?;
+            if let Ok(destroy) = destroy.dyn_into::<js_sys::Function>() {
+                destroy.call1(&redoc, &self.root)?;
+            }
+        }
+        self.root.remove();
+        Ok(())
+    }
 }
 
 #[wasm_bindgen]
@@ -29,7 +89,11 @@ impl RedocTryItOut {
         let document = window
             .document()
             .ok_or("should have a document on window")?;
-        Ok(RedocTryItOut { document })
+        Ok(RedocTryItOut {
+            document,
+            mounted: RefCell::default(),
+            generation: Cell::default(),
+        })
     }
 
     pub async fn init(
@@ -37,89 +101,166 @@ impl RedocTryItOut {
         doc_url: String,
         raw_config: JsValue,
         element: Option<Element>,
+    ) -> Result<(), JsValue> {
+        let doc_url = openapi::resolve_url(&self.document.url()?, &doc_url);
+        let document = openapi::fetch_document(&doc_url).await?;
+        self.render(&doc_url, document, raw_config, element).await
+    }
+
+    /// Like `init`, for a spec given as a JavaScript object or as JSON or YAML text.
+    /// Relative urls in the spec resolve against the page.
+    pub async fn init_with_spec(
+        &self,
+        spec: JsValue,
+        raw_config: JsValue,
+        element: Option<Element>,
+    ) -> Result<(), JsValue> {
+        let document = openapi::document_from_js(spec).map_err(|e| JsValue::from_str(&e))?;
+        let page_url = self.document.url()?;
+        self.render(&page_url, document, raw_config, element).await
+    }
+
+    /// Remove Redoc and the try-it-out panels from the page, with their listeners, and stop
+    /// renders that are still running. The styles go once no other instance is left on the page.
+    pub fn destroy(&self) -> Result<(), JsValue> {
+        self.generation.set(self.generation.get() + 1);
+        self.unmount()?;
+        if self.document.query_selector(&format!(".{}", REDOC_ROOT_CLASS))?.is_none() {
+            if let Some(style) = self.document.get_element_by_id(try_it_out::STYLE_ID) {
+                style.remove();
+            }
+        }
+        Ok(())
+    }
+
+    /// Render the spec at `doc_url` in place of the current one, with the same options.
+    pub async fn reload(&self, doc_url: String) -> Result<(), JsValue> {
+        let (raw_config, element) = self.mounted_with(|mounted| (mounted.raw_config.clone(), mounted.element.clone()))?;
+        let doc_url = openapi::resolve_url(&self.document.url()?, &doc_url);
+        let document = openapi::fetch_document(&doc_url).await?;
+        self.render(&doc_url, document, raw_config, element).await
+    }
+
+    /// Render the current spec again with other options. Redoc is not loaded again.
+    pub async fn update_options(&self, raw_config: JsValue) -> Result<(), JsValue> {
+        let (doc_url, spec, element) =
+            self.mounted_with(|mounted| (mounted.doc_url.clone(), mounted.spec.clone(), mounted.element.clone()))?;
+        self.render(&doc_url, spec, raw_config, element).await
+    }
+
+    fn unmount(&self) -> Result<(), JsValue> {
+        let mounted = self.mounted.borrow_mut().take();
+        match mounted {
+            Some(mounted) => mounted.unmount(),
+            None => Ok(()),
+        }
+    }
+
+    fn mounted_with<T>(&self, read: impl FnOnce(&Mounted) -> T) -> Result<T, JsValue> {
+        self.mounted
+            .borrow()
+            .as_ref()
+            .map(read)
+            .ok_or_else(|| JsValue::from_str("nothing is rendered, call init first"))
+    }
+
+    async fn render(
+        &self,
+        doc_url: &str,
+        document: serde_json::Value,
+        raw_config: JsValue,
+        element: Option<Element>,
     ) -> Result<(), JsValue> {
         let config: RedocTryItOutOptions = serde_wasm_bindgen::from_value(raw_config.clone())
             .map_err(|e| JsValue::from_str(&format!("Failed to parse config: {:?}", e)))?;
 
-        let redoc_config: RedocOptions = serde_wasm_bindgen::from_value(raw_config)
+        let redoc_config: RedocOptions = serde_wasm_bindgen::from_value(raw_config.clone())
             .map_err(|e| JsValue::from_str(&format!("Failed to parse redoc config: {:?}", e)))?;
 
-        self.add_script_tag(format!(
-            "https://cdn.jsdelivr.net/npm/redoc@{}/bundles/redoc.standalone.min.js",
-            config.redoc_version
-        ))
-        .await?;
+        // a later render or destroy while this one waits makes it stop at the next check
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        let superseded = || self.generation.get() != generation;
+
+        loader::load_redoc(&self.document, &config).await?;
+        if config.legacy_jquery {
+            loader::load_jquery(&self.document, &config).await?;
+        }
+
+        let spec = document.clone();
+        let document = openapi::bundle(doc_url, document).await?;
+        if superseded() {
+            return Ok(());
+        }
+        let spec_object = document
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize spec: {:?}", e)))?;
 
         let redoc_container = element
+            .clone()
             .or_else(|| {
                 self.document
                     .get_element_by_id(config.container_id.as_str())
             })
             .ok_or_else(|| JsValue::from_str("should have a redoc container"))?;
 
+        // a render replaces the previous one, e.g. when init runs again on a route change
+        self.unmount()?;
+        let root = dom::create(&self.document, "div", REDOC_ROOT_CLASS)?;
+        redoc_container.append_child(&root)?;
+        let root = Element::from(root);
+        *self.mounted.borrow_mut() = Some(Mounted {
+            doc_url: doc_url.to_string(),
+            spec,
+            raw_config: raw_config.clone(),
+            element,
+            root: root.clone(),
+            auth_dialog: None,
+            try_it_out: None,
+        });
+
         let options = serde_wasm_bindgen::to_value(&redoc_config)
             .map_err(|e| JsValue::from_str(&format!("Failed to serialize: {:?}", e)))?;
 
-        let init_promise = js_sys::Promise::new(&mut move |resolve, reject| {
-            let init_callback = Closure::wrap(Box::new(move |err: JsValue| {
-                if err.is_undefined() {
-                    resolve.call0(&JsValue::NULL).unwrap();
-                } else {
-                    reject.call1(&JsValue::NULL, &err).unwrap();
-                }
-            }) as Box<dyn FnMut(JsValue)>);
-
-            initRedoc(
-                doc_url.clone(),
-                options.clone(),
-                redoc_container.clone(),
-                init_callback.as_ref().unchecked_ref(),
-            );
-
-            init_callback.forget();
+        let mut settle = None;
+        let init_promise = js_sys::Promise::new(&mut |resolve, reject| settle = Some((resolve, reject)));
+        let (resolve, reject) = settle.ok_or_else(|| JsValue::from_str("should call the promise executor"))?;
+        // kept until Redoc called back, then dropped with this future
+        let init_callback = Closure::<dyn FnMut(JsValue)>::new(move |err: JsValue| {
+            if err.is_undefined() {
+                resolve.call0(&JsValue::NULL).unwrap();
+            } else {
+                reject.call1(&JsValue::NULL, &err).unwrap();
+            }
         });
+        initRedoc(spec_object, options, root.clone(), init_callback.as_ref().unchecked_ref());
 
         JsFuture::from(init_promise).await?;
-        Ok(())
-    }
+        if superseded() {
+            return Ok(());
+        }
 
-    async fn add_script_tag(&self, url: String) -> Result<(), JsValue> {
-        let script = self
-            .document
-            .create_element("script")?
-            .dyn_into::<HtmlScriptElement>()?;
-        script.set_src(&url);
-
-        let promise = js_sys::Promise::new(&mut |resolve, reject| {
-            let onload = Closure::wrap(Box::new(move |_| {
-                if let Err(e) = resolve.call0(&JsValue::NULL) {
-                    log(&format!("Failed to resolve: {:?}", e));
+        if config.try_it_out_enabled {
+            match Spec::new(doc_url, document) {
+                Ok(spec) => {
+                    let spec = Rc::new(spec);
+                    let auth = Rc::new(Auth::new(Rc::clone(&spec)));
+                    let auth_dialog = AuthDialog::mount(
+                        &self.document,
+                        &config.auth_btn,
+                        &config.oauth2,
+                        &root,
+                        Rc::clone(&auth),
+                    )?;
+                    let try_it_out = TryItOut::mount(&self.document, &config, &redoc_config, &root, spec, auth)?;
+                    if let Some(mounted) = self.mounted.borrow_mut().as_mut() {
+                        mounted.auth_dialog = auth_dialog;
+                        mounted.try_it_out = Some(try_it_out);
+                    }
                 }
-            }) as Box<dyn FnMut(JsValue)>);
-
-            let onerror = Closure::wrap(Box::new(move |_| {
-                if let Err(e) = reject.call0(&JsValue::NULL) {
-                    log(&format!("Failed to reject: {:?}", e));
-                }
-            }) as Box<dyn FnMut(JsValue)>);
-
-            script.set_onload(Some(onload.as_ref().unchecked_ref()));
-            script.set_onerror(Some(onerror.as_ref().unchecked_ref()));
-
-            onload.forget();
-            onerror.forget();
-        });
-
-        // Append the script to the body
-        let body = self
-            .document
-            .body()
-            .ok_or_else(|| JsValue::from_str("should have a body"))?;
-        body.append_child(&script)?;
-
-        // Wait for the script to load
-        JsFuture::from(promise).await?;
-
+                Err(e) => log(&format!("Try it out is disabled: {}", e)),
+            }
+        }
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,354 @@
+//! Loading the Redoc standalone bundle into the page.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{js_sys, Document, HtmlScriptElement};
+use super::options::RedocTryItOutOptions;
+
+const DEFAULT_ASSET_BASE_URL: &str = "https://cdn.jsdelivr.net/npm";
+/// The global the Redoc bundle defines.
+const REDOC_GLOBAL: &str = "Redoc";
+
+thread_local! {
+    /// Loads still running, by the global their script defines.
+    static PENDING: RefCell<HashMap<String, js_sys::Promise>> = RefCell::default();
+}
+
+/// Subresource Integrity of the published `redoc.standalone.js` by Redoc version, so
+/// that a tampered CDN or mirror is refused. The CDN's `.min.js` variants are minified on
+/// the fly and can not be pinned. Hashes are only added from a bundle
+/// downloaded and checked by hand, as printed by
+/// `curl -s <bundle url> | openssl dgst -sha384 -binary | openssl base64 -A`.
+const KNOWN_INTEGRITY: &[(&str, &str)] = &[];
+
+/// Attributes of an injected `<script>` tag.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScriptAttributes {
+    pub integrity: Option<String>,
+    pub cross_origin: Option<String>,
+    pub nonce: Option<String>,
+}
+
+/// The bundle built into the package by the `embedded-redoc` feature, see the README.
+#[cfg(feature = "embedded-redoc")]
+const EMBEDDED_REDOC: &str = include_str!("../vendor/redoc.standalone.js");
+
+/// Where the Redoc bundle is loaded from: `redocBundleUrl`, or the bundle of
+/// `redocVersion` below `assetBaseUrl`.
+pub fn bundle_url(config: &RedocTryItOutOptions) -> String {
+    if let Some(url) = &config.redoc_bundle_url {
+        return url.clone();
+    }
+    let base = config.asset_base_url.as_deref().unwrap_or(DEFAULT_ASSET_BASE_URL);
+    format!(
+        "{}/redoc@{}/bundles/redoc.standalone.js",
+        base.trim_end_matches('/'),
+        config.redoc_version
+    )
+}
+
+/// The attributes of the tag loading the Redoc bundle. Without `redocIntegrity`, the
+/// bundle of `redocVersion` is checked against its known hash; `strictIntegrity` refuses
+/// bundles whose hash is not known.
+pub fn bundle_attributes(config: &RedocTryItOutOptions) -> Result<ScriptAttributes, String> {
+    let known = || {
+        KNOWN_INTEGRITY
+            .iter()
+            .find(|(version, _)| *version == config.redoc_version)
+            .map(|(_, integrity)| integrity.to_string())
+    };
+    let integrity = match (&config.redoc_integrity, &config.redoc_bundle_url) {
+        (Some(integrity), _) => Some(integrity.clone()),
+        (None, None) => known(),
+        // a bundle of its own can not be compared with the published ones
+        (None, Some(_)) => None,
+    };
+    if integrity.is_none() && config.strict_integrity {
+        return Err(format!(
+            "The integrity of Redoc {} at {} is not known, set redocIntegrity to load it",
+            config.redoc_version,
+            bundle_url(config)
+        ));
+    }
+    // browsers only check the integrity of cross-origin scripts fetched with CORS
+    let cross_origin = match &integrity {
+        Some(_) => Some(config.cross_origin.clone().unwrap_or_else(|| "anonymous".to_string())),
+        None => config.cross_origin.clone(),
+    };
+    Ok(ScriptAttributes {
+        integrity,
+        cross_origin,
+        nonce: config.nonce.clone(),
+    })
+}
+
+/// Every url the Redoc bundle is loaded from until one succeeds: the bundle url and the
+/// fallbacks, each tried once more per retry.
+pub fn candidate_urls(config: &RedocTryItOutOptions) -> Vec<String> {
+    std::iter::once(bundle_url(config))
+        .chain(config.redoc_fallback_urls.iter().cloned())
+        .flat_map(|url| std::iter::repeat_n(url, config.script_retries as usize + 1))
+        .collect()
+}
+
+/// Load Redoc, from the embedded bundle unless `redocBundleUrl` asks for another one.
+/// Nothing is loaded when the page already has Redoc, and concurrent calls share one load.
+pub async fn load_redoc(document: &Document, config: &RedocTryItOutOptions) -> Result<(), JsValue> {
+    #[cfg(feature = "embedded-redoc")]
+    if config.redoc_bundle_url.is_none() {
+        if has_global(REDOC_GLOBAL) {
+            return Ok(());
+        }
+        return add_inline_script(document, EMBEDDED_REDOC, config.nonce.as_deref());
+    }
+    let attributes = bundle_attributes(config).map_err(|e| JsValue::from_str(&e))?;
+    load_script_once(document, REDOC_GLOBAL, candidate_urls(config), attributes, config.script_timeout_ms).await
+}
+
+/// Load jQuery and then jquery.scrollTo at `dependenciesVersions` from `assetBaseUrl`.
+/// Neither is used by this crate, they are only there for scripts of the page.
+pub async fn load_jquery(document: &Document, config: &RedocTryItOutOptions) -> Result<(), JsValue> {
+    let base = config.asset_base_url.as_deref().unwrap_or(DEFAULT_ASSET_BASE_URL).trim_end_matches('/');
+    let versions = &config.dependencies_versions;
+    let attributes = ScriptAttributes {
+        integrity: None,
+        cross_origin: config.cross_origin.clone(),
+        nonce: config.nonce.clone(),
+    };
+    let retries = config.script_retries as usize + 1;
+    let jquery = format!("{}/jquery@{}/dist/jquery.min.js", base, versions.jquery);
+    load_script_once(document, "jQuery", vec![jquery; retries], attributes.clone(), config.script_timeout_ms).await?;
+    let scroll_to = format!("{}/jquery.scrollto@{}/jquery.scrollTo.min.js", base, versions.jquery_scroll_to);
+    load_script_once(document, "jQuery.scrollTo", vec![scroll_to; retries], attributes, config.script_timeout_ms).await
+}
+
+/// Load the script defining the global `name`, unless it is defined already, trying
+/// `urls` in order. A load still running for `name` is waited for instead of starting another.
+pub async fn load_script_once(
+    document: &Document,
+    name: &str,
+    urls: Vec<String>,
+    attributes: ScriptAttributes,
+    timeout_ms: u32,
+) -> Result<(), JsValue> {
+    if has_global(name) {
+        return Ok(());
+    }
+    let pending = PENDING.with(|pending| pending.borrow().get(name).cloned());
+    let promise = match pending {
+        Some(promise) => promise,
+        None => {
+            let document = document.clone();
+            let promise = future_to_promise(async move {
+                let mut failure = JsValue::from_str("No url to load the script from");
+                for url in &urls {
+                    match add_script_tag(&document, url, &attributes, timeout_ms).await {
+                        Ok(()) => return Ok(JsValue::UNDEFINED),
+                        Err(e) => {
+                            web_sys::console::warn_1(&e);
+                            failure = e;
+                        }
+                    }
+                }
+                Err(failure)
+            });
+            PENDING.with(|pending| pending.borrow_mut().insert(name.to_string(), promise.clone()));
+            promise
+        }
+    };
+    let loaded = JsFuture::from(promise).await;
+    // the global answers once loaded, and a failed load may be tried again by the next call
+    PENDING.with(|pending| pending.borrow_mut().remove(name));
+    loaded.map(|_| ())
+}
+
+/// Whether the global `name` is defined; `a.b` is the property `b` of the global `a`.
+fn has_global(name: &str) -> bool {
+    let mut value = JsValue::from(js_sys::global());
+    for property in name.split('.') {
+        match js_sys::Reflect::get(&value, &JsValue::from_str(property)) {
+            Ok(found) if !found.is_undefined() && !found.is_null() => value = found,
+            _ => return false,
+        }
+    }
+    true
+}
+
+#[cfg(feature = "embedded-redoc")]
+fn add_inline_script(document: &Document, text: &str, nonce: Option<&str>) -> Result<(), JsValue> {
+    let script = document.create_element("script")?.dyn_into::<HtmlScriptElement>()?;
+    if let Some(nonce) = nonce {
+        script.set_attribute("nonce", nonce)?;
+    }
+    script.set_text(text)?;
+    document
+        .body()
+        .ok_or_else(|| JsValue::from_str("should have a body"))?
+        .append_child(&script)?;
+    Ok(())
+}
+
+/// Append a script tag for `url` and wait until it loaded. A tag that fails or takes more
+/// than `timeout_ms` is removed again.
+pub async fn add_script_tag(
+    document: &Document,
+    url: &str,
+    attributes: &ScriptAttributes,
+    timeout_ms: u32,
+) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no global `window` exists"))?;
+    let script = document.create_element("script")?.dyn_into::<HtmlScriptElement>()?;
+    if let Some(integrity) = &attributes.integrity {
+        script.set_integrity(integrity);
+    }
+    script.set_cross_origin(attributes.cross_origin.as_deref());
+    if let Some(nonce) = &attributes.nonce {
+        script.set_attribute("nonce", nonce)?;
+    }
+    script.set_src(url);
+
+    let mut settle = None;
+    let promise = js_sys::Promise::new(&mut |resolve, reject| settle = Some((resolve, reject)));
+    let (resolve, reject) = settle.ok_or_else(|| JsValue::from_str("should call the promise executor"))?;
+
+    // the closures live until the script settled and are dropped with this future
+    let onload = Closure::<dyn FnMut()>::new(move || {
+        if let Err(e) = resolve.call0(&JsValue::NULL) {
+            web_sys::console::log_1(&JsValue::from_str(&format!("Failed to resolve: {:?}", e)));
+        }
+    });
+    let fail = move |message: String| {
+        if let Err(e) = reject.call1(&JsValue::NULL, &JsValue::from_str(&message)) {
+            web_sys::console::log_1(&JsValue::from_str(&format!("Failed to reject: {:?}", e)));
+        }
+    };
+    let onerror = Closure::<dyn FnMut()>::new({
+        let fail = fail.clone();
+        let url = url.to_string();
+        move || fail(format!("Failed to load {}", url))
+    });
+    let ontimeout = Closure::<dyn FnMut()>::new({
+        let url = url.to_string();
+        move || fail(format!("Loading {} took more than {} ms", url, timeout_ms))
+    });
+
+    script.set_onload(Some(onload.as_ref().unchecked_ref()));
+    script.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    let timer = match timeout_ms {
+        0 => None,
+        _ => Some(window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            ontimeout.as_ref().unchecked_ref(),
+            timeout_ms.min(i32::MAX as u32) as i32,
+        )?),
+    };
+
+    let body = document
+        .body()
+        .ok_or_else(|| JsValue::from_str("should have a body"))?;
+    body.append_child(&script)?;
+
+    let loaded = JsFuture::from(promise).await;
+
+    if let Some(timer) = timer {
+        window.clear_timeout_with_handle(timer);
+    }
+    script.set_onload(None);
+    script.set_onerror(None);
+    if loaded.is_err() {
+        script.remove();
+    }
+    loaded.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_bundle_url() {
+        let mut config = RedocTryItOutOptions {
+            redoc_version: "2.1.3".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            "https://cdn.jsdelivr.net/npm/redoc@2.1.3/bundles/redoc.standalone.js",
+            bundle_url(&config)
+        );
+
+        config.asset_base_url = Some("https://npm.example.com/".to_string());
+        assert_eq!(
+            "https://npm.example.com/redoc@2.1.3/bundles/redoc.standalone.js",
+            bundle_url(&config)
+        );
+
+        config.redoc_bundle_url = Some("/assets/redoc.js".to_string());
+        assert_eq!("/assets/redoc.js", bundle_url(&config));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_candidate_urls() {
+        let config = RedocTryItOutOptions {
+            redoc_version: "2.1.3".to_string(),
+            redoc_bundle_url: Some("/assets/redoc.js".to_string()),
+            redoc_fallback_urls: vec!["https://unpkg.com/redoc@2.1.3/bundles/redoc.standalone.js".to_string()],
+            script_retries: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![
+                "/assets/redoc.js",
+                "/assets/redoc.js",
+                "https://unpkg.com/redoc@2.1.3/bundles/redoc.standalone.js",
+                "https://unpkg.com/redoc@2.1.3/bundles/redoc.standalone.js",
+            ],
+            candidate_urls(&config)
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    #[ignore = "the sha384 of the default Redoc bundle still has to be added to KNOWN_INTEGRITY"]
+    fn test_default_version_integrity() {
+        let config = RedocTryItOutOptions::default();
+        let attributes = bundle_attributes(&config).unwrap();
+
+        assert!(attributes.integrity.is_some_and(|integrity| integrity.starts_with("sha384-")));
+        assert_eq!(Some("anonymous".to_string()), attributes.cross_origin);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_bundle_attributes() {
+        let mut config = RedocTryItOutOptions {
+            redoc_version: "0.0.1".to_string(),
+            nonce: Some("r4nd0m".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Ok(ScriptAttributes {
+                integrity: None,
+                cross_origin: None,
+                nonce: Some("r4nd0m".to_string()),
+            }),
+            bundle_attributes(&config)
+        );
+
+        config.strict_integrity = true;
+        assert_eq!(
+            Err("The integrity of Redoc 0.0.1 at https://cdn.jsdelivr.net/npm/redoc@0.0.1/bundles/redoc.standalone.js is not known, set redocIntegrity to load it".to_string()),
+            bundle_attributes(&config)
+        );
+
+        config.redoc_integrity = Some("sha384-abc".to_string());
+        assert_eq!(
+            Ok(ScriptAttributes {
+                integrity: Some("sha384-abc".to_string()),
+                cross_origin: Some("anonymous".to_string()),
+                nonce: Some("r4nd0m".to_string()),
+            }),
+            bundle_attributes(&config)
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use serde_json::Value;
+use super::{coerce, is_json, validate, MediaType, OperationRef, Response, Spec, Violation};
+
+/// How a received response compares to the responses documented for its operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseReport {
+    /// The `responses` key the status matched: the code itself, its range (`4XX`) or `default`.
+    pub documented: Option<String>,
+    pub violations: Vec<Violation>,
+}
+
+impl ResponseReport {
+    pub fn conforms(&self) -> bool {
+        self.documented.is_some() && self.violations.is_empty()
+    }
+}
+
+/// Compare a response of `operation` with its documented status codes, headers and body schema.
+pub fn check_response(
+    spec: &Spec,
+    operation: &OperationRef,
+    status: u16,
+    headers: &[(String, String)],
+    body: &str,
+) -> ResponseReport {
+    let responses = &operation.operation.responses;
+    let status_text = status.to_string();
+    let range = format!("{}XX", status / 100);
+    let documented = responses
+        .keys()
+        .find(|key| **key == status_text)
+        .or_else(|| responses.keys().find(|key| key.eq_ignore_ascii_case(&range)))
+        .or_else(|| responses.keys().find(|key| *key == "default"))
+        .cloned();
+    let Some(response) = documented.as_ref().and_then(|key| spec.resolve(&responses[key])) else {
+        return ResponseReport {
+            documented,
+            violations: Vec::new(),
+        };
+    };
+
+    let mut violations = check_headers(spec, &response, headers);
+    violations.extend(check_body(spec, &response, header(headers, "content-type"), body));
+    ResponseReport { documented, violations }
+}
+
+fn check_headers(spec: &Spec, response: &Response, headers: &[(String, String)]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (name, documented) in &response.headers {
+        // Content-Type is described by `content`, and is ignored here as the specification says
+        if name.eq_ignore_ascii_case("content-type") {
+            continue;
+        }
+        let Some(documented) = spec.resolve(documented) else {
+            continue;
+        };
+        let path = format!("header {}", name);
+        match (header(headers, name), &documented.schema) {
+            (None, _) if documented.required => violations.push(Violation {
+                path,
+                message: "is required".to_string(),
+            }),
+            (Some(value), Some(schema)) => violations.extend(
+                validate(spec, schema, &coerce(spec, schema, value))
+                    .into_iter()
+                    .map(|violation| prefixed(&path, violation)),
+            ),
+            _ => {}
+        }
+    }
+    violations
+}
+
+fn check_body(spec: &Spec, response: &Response, content_type: Option<&str>, body: &str) -> Vec<Violation> {
+    if response.content.is_empty() || body.is_empty() {
+        return Vec::new();
+    }
+    let content_type = content_type.unwrap_or_default();
+    let Some((media_type, media)) = find_media_type(response, content_type) else {
+        return vec![Violation {
+            path: "body".to_string(),
+            message: format!("content type {} is not documented", content_type),
+        }];
+    };
+    let Some(schema) = &media.schema else {
+        return Vec::new();
+    };
+    if !is_json(media_type) && !is_json(content_type) {
+        return Vec::new();
+    }
+    match serde_json::from_str::<Value>(body) {
+        Ok(value) => validate(spec, schema, &value)
+            .into_iter()
+            .map(|violation| prefixed("body", violation))
+            .collect(),
+        Err(e) => vec![Violation {
+            path: "body".to_string(),
+            message: format!("is not valid JSON: {}", e),
+        }],
+    }
+}
+
+/// The documented media type `content_type` falls under, trying exact matches before wildcards.
+fn find_media_type<'a>(response: &'a Response, content_type: &str) -> Option<(&'a String, &'a MediaType)> {
+    let received = essence(content_type);
+    let (main_type, _) = received.split_once('/').unwrap_or((&received, ""));
+    let wildcard = format!("{}/*", main_type);
+    let candidates = [received.as_str(), wildcard.as_str(), "*/*"];
+    let found = candidates
+        .into_iter()
+        .find_map(|wanted| response.content.iter().find(|(media_type, _)| essence(media_type) == wanted));
+    found
+}
+
+fn essence(media_type: &str) -> String {
+    media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn prefixed(prefix: &str, violation: Violation) -> Violation {
+    Violation {
+        path: format!("{}{}", prefix, violation.path),
+        message: violation.message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn spec() -> Spec {
+        Spec::new(
+            "https://example.com/openapi.json",
+            json!({
+                "openapi": "3.0.3",
+                "paths": {
+                    "/pet/{petId}": {
+                        "get": {
+                            "operationId": "getPet",
+                            "responses": {
+                                "200": {
+                                    "description": "The pet",
+                                    "headers": {
+                                        "X-Rate-Limit": { "required": true, "schema": { "type": "integer", "minimum": 0 } }
+                                    },
+                                    "content": {
+                                        "application/json": {
+                                            "schema": {
+                                                "type": "object",
+                                                "required": ["name"],
+                                                "properties": { "name": { "type": "string" }, "age": { "type": "integer" } }
+                                            }
+                                        }
+                                    }
+                                },
+                                "4XX": { "$ref": "#/components/responses/Problem" }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "responses": {
+                        "Problem": {
+                            "description": "A problem",
+                            "content": { "application/problem+json": { "schema": { "type": "object" } } }
+                        }
+                    }
+                }
+            }),
+        )
+        .unwrap()
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    fn messages(report: &ResponseReport) -> Vec<String> {
+        report.violations.iter().map(Violation::to_string).collect()
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_conforming_response() {
+        let spec = spec();
+        let operation = spec.find_operation_by_id("getPet").unwrap();
+        let headers = headers(&[("content-type", "application/json; charset=utf-8"), ("x-rate-limit", "10")]);
+
+        let report = check_response(&spec, &operation, 200, &headers, r#"{ "name": "Rex" }"#);
+        assert_eq!(Some("200".to_string()), report.documented);
+        assert!(report.conforms());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_violations() {
+        let spec = spec();
+        let operation = spec.find_operation_by_id("getPet").unwrap();
+
+        let report = check_response(
+            &spec,
+            &operation,
+            200,
+            &headers(&[("Content-Type", "application/json")]),
+            r#"{ "age": "old" }"#,
+        );
+        assert_eq!(
+            vec!["header X-Rate-Limit: is required", "body/name: is required", "body/age: must be integer"],
+            messages(&report)
+        );
+
+        let report = check_response(&spec, &operation, 200, &headers(&[("X-Rate-Limit", "-1")]), "");
+        assert_eq!(vec!["header X-Rate-Limit: must be at least 0"], messages(&report));
+
+        let report = check_response(&spec, &operation, 200, &headers(&[("X-Rate-Limit", "1"), ("Content-Type", "text/html")]), "<p>");
+        assert_eq!(vec!["body: content type text/html is not documented"], messages(&report));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_status_ranges() {
+        let spec = spec();
+        let operation = spec.find_operation_by_id("getPet").unwrap();
+        let problem = headers(&[("Content-Type", "application/problem+json")]);
+
+        let report = check_response(&spec, &operation, 404, &problem, "[]");
+        assert_eq!(Some("4XX".to_string()), report.documented);
+        assert_eq!(vec!["body: must be object"], messages(&report));
+
+        let report = check_response(&spec, &operation, 500, &problem, "{}");
+        assert_eq!(None, report.documented);
+        assert!(!report.conforms());
+    }
+}
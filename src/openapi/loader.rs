@@ -0,0 +1,81 @@
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+use crate::http::{self, HttpRequest};
+
+/// Parse a JSON or YAML document.
+pub fn parse_document(text: &str) -> Result<Value, String> {
+    let document = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).map_err(|e| format!("Invalid JSON document: {}", e))?
+    } else {
+        serde_yaml::from_str(text).map_err(|e| format!("Invalid YAML document: {}", e))?
+    };
+    expect_object(document)
+}
+
+/// Read a document handed over from JavaScript, either as an object or as JSON or YAML text.
+pub fn document_from_js(value: JsValue) -> Result<Value, String> {
+    match value.as_string() {
+        Some(text) => parse_document(&text),
+        None => serde_wasm_bindgen::from_value(value)
+            .map_err(|e| format!("Invalid spec object: {}", e))
+            .and_then(expect_object),
+    }
+}
+
+/// A document is an object at the top; anything else would render an empty page.
+fn expect_object(document: Value) -> Result<Value, String> {
+    match document {
+        Value::Object(_) => Ok(document),
+        _ => Err(format!("Invalid document: expected an object, got {}", document)),
+    }
+}
+
+/// Fetch the document at `url` and parse it.
+pub async fn fetch_document(url: &str) -> Result<Value, JsValue> {
+    let mut request = HttpRequest::new("GET", url);
+    request.set_header("Accept", "application/json, application/yaml;q=0.9, */*;q=0.8");
+    let response = http::execute(&request).await?;
+    if response.status >= 400 {
+        return Err(JsValue::from_str(&format!(
+            "Failed to load {}: {} {}",
+            url, response.status, response.status_text
+        )));
+    }
+    parse_document(&response.body).map_err(|e| JsValue::from_str(&format!("Failed to parse {}: {}", url, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_parse_json_and_yaml() {
+        let json = parse_document(r#"{ "openapi": "3.0.3", "paths": { "/b": {}, "/a": {} } }"#).unwrap();
+        let yaml = parse_document("openapi: 3.0.3\npaths:\n  /b: {}\n  /a: {}\n").unwrap();
+
+        assert_eq!(json, yaml);
+        // path order is kept as written
+        let paths: Vec<_> = yaml["paths"].as_object().unwrap().keys().cloned().collect();
+        assert_eq!(vec!["/b".to_string(), "/a".to_string()], paths);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_from_js() {
+        let object = web_sys::js_sys::JSON::parse(r#"{ "openapi": "3.0.3", "paths": {} }"#).unwrap();
+        let text = JsValue::from_str("openapi: 3.0.3\npaths: {}\n");
+
+        assert_eq!(document_from_js(object).unwrap(), document_from_js(text).unwrap());
+        assert!(document_from_js(JsValue::from_f64(1.0)).is_err());
+        assert!(document_from_js(web_sys::js_sys::Array::new().into()).is_err());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_parse_invalid_document() {
+        assert!(parse_document("{ not json").is_err());
+        assert!(parse_document("openapi: [unclosed").is_err());
+        assert_eq!(Err("Invalid document: expected an object, got 42".to_string()), parse_document("42"));
+        assert!(parse_document("- openapi: 3.0.3").is_err());
+    }
+}
@@ -0,0 +1,599 @@
+//! The OpenAPI document as seen by try it out.
+
+mod conformance;
+mod loader;
+mod model;
+mod resolver;
+mod sample;
+mod swagger2;
+mod validation;
+
+use std::borrow::Cow;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+pub use conformance::check_response;
+pub use loader::{document_from_js, fetch_document};
+pub use model::*;
+pub use resolver::{bundle, resolve_url};
+pub use sample::{body_examples, is_json, request_sample, BodyExamples, SampleOptions};
+pub use validation::{coerce, validate, Violation};
+
+/// A loaded OpenAPI document together with its typed model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spec {
+    /// Where the document was loaded from; relative server urls resolve against it.
+    pub url: String,
+    pub document: Value,
+    pub api: OpenApi,
+}
+
+/// The operation a request was sent to, see `Spec::match_request`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestMatch<'a> {
+    pub operation: OperationRef<'a>,
+    /// Values of the path templates, decoded.
+    pub path_values: Vec<(String, String)>,
+    /// What comes before the operation path in the url.
+    pub base_url: String,
+}
+
+/// An operation of the spec and where it lives.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OperationRef<'a> {
+    /// Lowercase http method.
+    pub method: &'static str,
+    pub path: &'a str,
+    pub path_item: &'a PathItem,
+    pub operation: &'a Operation,
+}
+
+impl Spec {
+    /// Parse `document`, upgrading Swagger 2.0 documents to OpenAPI 3.0 first.
+    pub fn new(url: &str, document: Value) -> Result<Spec, String> {
+        let document = if swagger2::is_swagger2(&document) {
+            swagger2::upgrade(url, &document)
+        } else {
+            document
+        };
+        let api = OpenApi::deserialize(&document).map_err(|e| format!("Invalid OpenAPI document: {}", e))?;
+        Ok(Spec {
+            url: url.to_string(),
+            document,
+            api,
+        })
+    }
+
+    pub fn operations(&self) -> impl Iterator<Item = OperationRef<'_>> {
+        self.api.paths.iter().flat_map(|(path, path_item)| {
+            path_item.operations().map(move |(method, operation)| OperationRef {
+                method,
+                path,
+                path_item,
+                operation,
+            })
+        })
+    }
+
+    pub fn find_operation(&self, method: &str, path: &str) -> Option<OperationRef<'_>> {
+        self.operations()
+            .find(|operation| operation.method.eq_ignore_ascii_case(method) && operation.path == path)
+    }
+
+    pub fn find_operation_by_id(&self, operation_id: &str) -> Option<OperationRef<'_>> {
+        self.operations()
+            .find(|operation| operation.operation.operation_id.as_deref() == Some(operation_id))
+    }
+
+    /// Find the operation Redoc rendered under `section_id`, which is either
+    /// `…operation/<operationId>` or `…paths/<escaped path>/<method>`.
+    pub fn find_operation_by_section_id(&self, section_id: &str) -> Option<OperationRef<'_>> {
+        // paths go first, they may well contain `operation/`
+        let pointer = section_id
+            .strip_prefix("paths/")
+            .or_else(|| Some(&section_id[section_id.find("/paths/")? + "/paths/".len()..]));
+        let by_path = pointer.and_then(|pointer| {
+            let (path, method) = pointer.rsplit_once('/')?;
+            self.find_operation(method, &unescape_pointer_token(path))
+        });
+        by_path.or_else(|| {
+            let start = section_id.rfind("operation/")?;
+            self.find_operation_by_id(&section_id[start + "operation/".len()..])
+        })
+    }
+
+    /// The url of `path` on the server at `server_url`, which may be relative to the document.
+    pub fn operation_url(&self, server_url: &str, path: &str) -> String {
+        let base = resolve_url(&self.url, server_url.trim());
+        format!("{}{}", base.trim_end_matches('/'), path)
+    }
+
+    /// The operation whose path ends the path of `url`, preferring operations with
+    /// the same method and then paths with more literal segments.
+    pub fn match_request(&self, method: &str, url: &str) -> Option<RequestMatch<'_>> {
+        self.operations()
+            .filter_map(|operation| {
+                let found = self.match_url(operation, url)?;
+                let template = operation.path.split('/').filter(|segment| !segment.is_empty());
+                let literals = template.clone().filter(|segment| !segment.starts_with('{')).count();
+                let score = (operation.method.eq_ignore_ascii_case(method), literals, template.count());
+                Some((score, found))
+            })
+            .fold(None, |best: Option<((bool, usize, usize), RequestMatch)>, (score, found)| match best {
+                Some((best_score, _)) if best_score >= score => best,
+                _ => Some((score, found)),
+            })
+            .map(|(_, found)| found)
+    }
+
+    /// How `url` calls `operation`, if its path ends with the path of the operation.
+    pub fn match_url<'a>(&self, operation: OperationRef<'a>, url: &str) -> Option<RequestMatch<'a>> {
+        let mut parsed = url::Url::parse(url).ok()?;
+        let segments: Vec<String> = parsed
+            .path_segments()?
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect();
+        let template: Vec<&str> = operation.path.split('/').filter(|segment| !segment.is_empty()).collect();
+        let start = segments.len().checked_sub(template.len())?;
+        let mut path_values = Vec::new();
+        for (expected, actual) in template.iter().zip(&segments[start..]) {
+            match expected.strip_prefix('{').and_then(|name| name.strip_suffix('}')) {
+                Some(name) => {
+                    // `+` is a plus in paths, only forms read it as a space
+                    let decoded = crate::http::decode_component(&actual.replace('+', "%2B"));
+                    path_values.push((name.to_string(), decoded));
+                }
+                None if expected == actual => {}
+                None => return None,
+            }
+        }
+        parsed.set_path(&segments[..start].join("/"));
+        parsed.set_query(None);
+        parsed.set_fragment(None);
+        Some(RequestMatch {
+            operation,
+            path_values,
+            base_url: parsed.as_str().trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Follow `item` to the object it references.
+    pub fn resolve<'a, T: Component>(&'a self, item: &'a RefOr<T>) -> Option<Cow<'a, T>> {
+        let mut item = Cow::Borrowed(item);
+        // refs may point at other refs, but never endlessly
+        for _ in 0..16 {
+            let reference = match item {
+                Cow::Borrowed(RefOr::Item(value)) => return Some(Cow::Borrowed(value)),
+                Cow::Owned(RefOr::Item(value)) => return Some(Cow::Owned(value)),
+                Cow::Borrowed(RefOr::Ref { reference }) => reference.clone(),
+                Cow::Owned(RefOr::Ref { reference }) => reference,
+            };
+            let component = reference
+                .strip_prefix(T::POINTER_PREFIX)
+                .filter(|name| !name.contains('/'));
+            item = match component {
+                Some(name) => Cow::Borrowed(T::components(&self.api.components).get(&unescape_pointer_token(name))?),
+                None => Cow::Owned(RefOr::deserialize(self.document.pointer(reference.strip_prefix('#')?)?).ok()?),
+            };
+        }
+        None
+    }
+
+    /// Follow the `$ref` of `schema`, if it has one.
+    pub fn resolve_schema<'a>(&'a self, schema: &'a Schema) -> Cow<'a, Schema> {
+        let mut current = Cow::Borrowed(schema);
+        for _ in 0..16 {
+            let Some(reference) = current.reference.clone() else {
+                return current;
+            };
+            match self.follow_schema_ref(&reference) {
+                Some(next) => current = next,
+                None => return current,
+            }
+        }
+        current
+    }
+
+    /// `schema` with every `$ref` replaced by its target, except for refs back
+    /// into a schema that is being replaced; those stay refs and can be
+    /// followed lazily with `resolve_schema`.
+    pub fn dereference_schema(&self, schema: &Schema) -> Schema {
+        self.dereference_with(schema, &mut Vec::new())
+    }
+
+    fn dereference_with(&self, schema: &Schema, seen: &mut Vec<String>) -> Schema {
+        if let Some(reference) = &schema.reference {
+            if seen.contains(reference) {
+                return schema.clone();
+            }
+            let Some(target) = self.follow_schema_ref(reference) else {
+                return schema.clone();
+            };
+            seen.push(reference.clone());
+            let dereferenced = self.dereference_with(&target, seen);
+            seen.pop();
+            return dereferenced;
+        }
+
+        let mut schema = schema.clone();
+        for property in schema.properties.values_mut() {
+            *property = self.dereference_with(property, seen);
+        }
+        if let Some(items) = &mut schema.items {
+            **items = self.dereference_with(items, seen);
+        }
+        if let Some(AdditionalProperties::Schema(additional)) = &mut schema.additional_properties {
+            **additional = self.dereference_with(additional, seen);
+        }
+        if let Some(not) = &mut schema.not {
+            **not = self.dereference_with(not, seen);
+        }
+        for composed in [&mut schema.all_of, &mut schema.one_of, &mut schema.any_of, &mut schema.prefix_items] {
+            for member in composed.iter_mut() {
+                *member = self.dereference_with(member, seen);
+            }
+        }
+        schema
+    }
+
+    fn follow_schema_ref(&self, reference: &str) -> Option<Cow<'_, Schema>> {
+        let component = reference
+            .strip_prefix("#/components/schemas/")
+            .filter(|name| !name.contains('/'));
+        match component {
+            Some(name) => self.api.components.schemas.get(&unescape_pointer_token(name)).map(Cow::Borrowed),
+            None => reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.document.pointer(pointer))
+                .and_then(|value| Schema::deserialize(value).ok())
+                .map(Cow::Owned),
+        }
+    }
+}
+
+impl<'a> OperationRef<'a> {
+    /// Path level parameters overridden by the operation level ones.
+    pub fn parameters(&self, spec: &'a Spec) -> Vec<Cow<'a, Parameter>> {
+        let mut parameters: Vec<Cow<Parameter>> = self
+            .path_item
+            .parameters
+            .iter()
+            .filter_map(|parameter| spec.resolve(parameter))
+            .collect();
+        for parameter in self.operation.parameters.iter().filter_map(|parameter| spec.resolve(parameter)) {
+            parameters.retain(|existing| existing.name != parameter.name || existing.location != parameter.location);
+            parameters.push(parameter);
+        }
+        parameters
+    }
+
+    pub fn request_body(&self, spec: &'a Spec) -> Option<Cow<'a, RequestBody>> {
+        self.operation.request_body.as_ref().and_then(|body| spec.resolve(body))
+    }
+
+    /// Operation servers, falling back to the path and global ones, and to `/`
+    /// when there are none at all, as the specification says.
+    pub fn servers(&self, spec: &'a Spec) -> Cow<'a, [Server]> {
+        [&self.operation.servers, &self.path_item.servers, &spec.api.servers]
+            .into_iter()
+            .find(|servers| !servers.is_empty())
+            .map(|servers| Cow::Borrowed(servers.as_slice()))
+            .unwrap_or_else(|| {
+                Cow::Owned(vec![Server {
+                    url: "/".to_string(),
+                    ..Default::default()
+                }])
+            })
+    }
+
+    /// Operation requirements, falling back to the global ones.
+    pub fn security(&self, spec: &'a Spec) -> &'a [SecurityRequirement] {
+        self.operation.security.as_deref().unwrap_or(&spec.api.security)
+    }
+}
+
+/// Objects that can be referenced from `components`.
+pub trait Component: DeserializeOwned + Clone {
+    const POINTER_PREFIX: &'static str;
+    fn components(components: &Components) -> &indexmap::IndexMap<String, RefOr<Self>>;
+}
+
+impl Component for Parameter {
+    const POINTER_PREFIX: &'static str = "#/components/parameters/";
+    fn components(components: &Components) -> &indexmap::IndexMap<String, RefOr<Self>> {
+        &components.parameters
+    }
+}
+
+impl Component for RequestBody {
+    const POINTER_PREFIX: &'static str = "#/components/requestBodies/";
+    fn components(components: &Components) -> &indexmap::IndexMap<String, RefOr<Self>> {
+        &components.request_bodies
+    }
+}
+
+impl Component for Response {
+    const POINTER_PREFIX: &'static str = "#/components/responses/";
+    fn components(components: &Components) -> &indexmap::IndexMap<String, RefOr<Self>> {
+        &components.responses
+    }
+}
+
+impl Component for Header {
+    const POINTER_PREFIX: &'static str = "#/components/headers/";
+    fn components(components: &Components) -> &indexmap::IndexMap<String, RefOr<Self>> {
+        &components.headers
+    }
+}
+
+impl Component for Example {
+    const POINTER_PREFIX: &'static str = "#/components/examples/";
+    fn components(components: &Components) -> &indexmap::IndexMap<String, RefOr<Self>> {
+        &components.examples
+    }
+}
+
+/// Undo the JSON pointer escaping (RFC 6901) of a single token.
+pub fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    const PETSTORE: &str = r###"
+openapi: 3.0.3
+info:
+  title: Petstore
+  version: 1.0.0
+servers:
+  - url: https://petstore.example.com/v1
+paths:
+  /pet/{petId}:
+    parameters:
+      - $ref: '#/components/parameters/PetId'
+      - name: trace
+        in: header
+        schema:
+          type: string
+    get:
+      operationId: getPetById
+      parameters:
+        - name: trace
+          in: header
+          required: true
+          schema:
+            type: string
+      responses:
+        '200':
+          description: found
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+  /store/inventory:
+    servers:
+      - url: '{scheme}://{region}.store.example.com:{port}'
+        variables:
+          scheme:
+            enum: [https, http]
+            default: https
+          region:
+            default: eu
+          port:
+            default: '443'
+      - url: /local
+    get:
+      responses:
+        '200':
+          $ref: '#/components/responses/Inventory'
+    post:
+      requestBody:
+        $ref: '#/components/requestBodies/Pet'
+      responses: {}
+components:
+  parameters:
+    PetId:
+      name: petId
+      in: path
+      required: true
+      schema:
+        type: integer
+        format: int64
+  requestBodies:
+    Pet:
+      required: true
+      content:
+        application/json:
+          schema:
+            $ref: '#/components/schemas/Pet'
+  responses:
+    Inventory:
+      description: inventory
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        name:
+          type: string
+        tag:
+          $ref: '#/components/schemas/Pet/properties/name'
+"###;
+
+    fn spec() -> Spec {
+        Spec::new("https://example.com/openapi.yaml", loader::parse_document(PETSTORE).unwrap()).unwrap()
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_operations() {
+        let spec = spec();
+        let operations: Vec<_> = spec
+            .operations()
+            .map(|operation| (operation.method, operation.path))
+            .collect();
+
+        assert_eq!(
+            vec![("get", "/pet/{petId}"), ("get", "/store/inventory"), ("post", "/store/inventory")],
+            operations
+        );
+        assert_eq!("Petstore", spec.api.info.title);
+        assert_eq!("https://petstore.example.com/v1", spec.api.servers[0].url);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_find_operation_by_section_id() {
+        let spec = spec();
+
+        let by_id = spec.find_operation_by_section_id("tag/pet/operation/getPetById").unwrap();
+        assert_eq!(("get", "/pet/{petId}"), (by_id.method, by_id.path));
+
+        let by_pointer = spec.find_operation_by_section_id("tag/store/paths/~1store~1inventory/post").unwrap();
+        assert_eq!(("post", "/store/inventory"), (by_pointer.method, by_pointer.path));
+
+        assert!(spec.find_operation_by_section_id("tag/pet").is_none());
+
+        let document = serde_json::json!({ "openapi": "3.1.0", "paths": { "/operation": { "get": {} } } });
+        let spec = Spec::new("https://example.com/openapi.json", document).unwrap();
+        for section_id in ["paths/~1operation/get", "tag/misc/paths/~1operation/get"] {
+            let by_pointer = spec.find_operation_by_section_id(section_id).unwrap();
+            assert_eq!(("get", "/operation"), (by_pointer.method, by_pointer.path));
+        }
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_parameters_are_resolved_and_overridden() {
+        let spec = spec();
+        let operation = spec.find_operation_by_id("getPetById").unwrap();
+        let parameters: Vec<_> = operation
+            .parameters(&spec)
+            .into_iter()
+            .map(|parameter| (parameter.name.clone(), parameter.location, parameter.required))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("petId".to_string(), ParameterLocation::Path, true),
+                ("trace".to_string(), ParameterLocation::Header, true),
+            ],
+            parameters
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_resolve_components() {
+        let spec = spec();
+        let post = spec.find_operation("POST", "/store/inventory").unwrap();
+        let body = post.request_body(&spec).unwrap();
+        assert!(body.required);
+
+        let schema = body.content["application/json"].schema.as_ref().unwrap();
+        let pet = spec.resolve_schema(schema);
+        assert_eq!(Some("object"), pet.primary_type());
+        assert_eq!(vec!["name".to_string()], pet.required);
+
+        let tag = spec.resolve_schema(&pet.properties["tag"]);
+        assert_eq!(Some("string"), tag.primary_type());
+
+        let get = spec.find_operation("get", "/store/inventory").unwrap();
+        let response = spec.resolve(&get.operation.responses["200"]).unwrap();
+        assert_eq!("inventory", response.description);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_match_request() {
+        let spec = spec();
+
+        let found = spec.match_request("GET", "https://api.example.com/v1/pet/a%20b?x=1").unwrap();
+        assert_eq!(Some("getPetById"), found.operation.operation.operation_id.as_deref());
+        assert_eq!(vec![("petId".to_string(), "a b".to_string())], found.path_values);
+        assert_eq!("https://api.example.com/v1", found.base_url);
+
+        let found = spec.match_request("POST", "http://localhost:8080/store/inventory").unwrap();
+        assert_eq!("post", found.operation.method);
+        assert_eq!("http://localhost:8080", found.base_url);
+
+        // without an operation for the method, the path alone decides
+        let found = spec.match_request("DELETE", "http://localhost/store/inventory").unwrap();
+        assert_eq!("/store/inventory", found.operation.path);
+        assert_eq!(None, spec.match_request("GET", "http://localhost/owners"));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_servers() {
+        let spec = spec();
+        let get_pet = spec.find_operation_by_id("getPetById").unwrap();
+        let servers = get_pet.servers(&spec);
+        assert_eq!(vec!["https://petstore.example.com/v1"], servers.iter().map(|server| server.url.as_str()).collect::<Vec<_>>());
+        assert_eq!("https://petstore.example.com/v1/pet/{petId}", spec.operation_url(&servers[0].expand(&[]), "/pet/{petId}"));
+
+        let inventory = spec.find_operation("get", "/store/inventory").unwrap();
+        let servers = inventory.servers(&spec);
+        assert_eq!("https://eu.store.example.com:443", servers[0].expand(&[]));
+        assert_eq!(
+            "http://us.store.example.com:443",
+            servers[0].expand(&[("scheme".to_string(), "http".to_string()), ("region".to_string(), "us".to_string())])
+        );
+        assert_eq!("https://example.com/local/store/inventory", spec.operation_url(&servers[1].expand(&[]), "/store/inventory"));
+
+        let empty = Spec::new("https://example.com/docs/openapi.json", serde_json::json!({ "openapi": "3.1.0", "paths": { "/a": { "get": {} } } })).unwrap();
+        let operation = empty.find_operation("get", "/a").unwrap();
+        assert_eq!("https://example.com/a", empty.operation_url(&operation.servers(&empty)[0].url, "/a"));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_dereference_circular_schema() {
+        let document = serde_json::json!({
+            "openapi": "3.0.3",
+            "components": {
+                "schemas": {
+                    "Node": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "$ref": "#/components/schemas/Name" },
+                            "children": { "type": "array", "items": { "$ref": "#/components/schemas/Node" } }
+                        }
+                    },
+                    "Name": { "type": "string" }
+                }
+            }
+        });
+        let spec = Spec::new("https://example.com/openapi.json", document).unwrap();
+        let root = Schema {
+            reference: Some("#/components/schemas/Node".to_string()),
+            ..Default::default()
+        };
+
+        let node = spec.dereference_schema(&root);
+        assert_eq!(Some("string"), node.properties["name"].primary_type());
+        let children = node.properties["children"].items.as_ref().unwrap();
+        assert_eq!(Some("#/components/schemas/Node".to_string()), children.reference);
+        assert_eq!(Some("object"), spec.resolve_schema(children).primary_type());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_swagger_is_upgraded() {
+        let document = serde_json::json!({
+            "swagger": "2.0",
+            "host": "petstore.swagger.io",
+            "paths": {
+                "/pet": {
+                    "post": {
+                        "operationId": "addPet",
+                        "parameters": [{ "in": "body", "name": "body", "required": true, "schema": { "type": "object" } }]
+                    }
+                }
+            }
+        });
+        let spec = Spec::new("https://petstore.swagger.io/v2/swagger.json", document).unwrap();
+
+        assert_eq!("https://petstore.swagger.io", spec.api.servers[0].url);
+        let add_pet = spec.find_operation_by_id("addPet").unwrap();
+        assert!(add_pet.request_body(&spec).unwrap().required);
+    }
+}
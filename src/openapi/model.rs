@@ -0,0 +1,653 @@
+use std::collections::BTreeMap;
+use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+/// Scheme names mapped to the scopes they require.
+pub type SecurityRequirement = BTreeMap<String, Vec<String>>;
+
+/// Either an inline object or a `$ref` to one.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum RefOr<T> {
+    Ref {
+        #[serde(rename = "$ref")]
+        reference: String,
+    },
+    Item(T),
+}
+
+/// The root of an OpenAPI 3.x document.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenApi {
+    pub openapi: String,
+    pub json_schema_dialect: Option<String>,
+    #[serde(default)]
+    pub info: Info,
+    #[serde(default)]
+    pub servers: Vec<Server>,
+    #[serde(default)]
+    pub paths: IndexMap<String, PathItem>,
+    /// Requests the API sends, keyed by name (3.1).
+    #[serde(default)]
+    pub webhooks: IndexMap<String, PathItem>,
+    #[serde(default)]
+    pub components: Components,
+    #[serde(default)]
+    pub security: Vec<SecurityRequirement>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub version: String,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Server {
+    pub url: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub variables: IndexMap<String, ServerVariable>,
+}
+
+impl Server {
+    /// `url` with its `{variables}` replaced by `values`, or by their defaults when not given.
+    pub fn expand(&self, values: &[(String, String)]) -> String {
+        self.variables.iter().fold(self.url.clone(), |url, (name, variable)| {
+            let value = values
+                .iter()
+                .find(|(candidate, _)| candidate == name)
+                .map_or(variable.default.as_str(), |(_, value)| value.as_str());
+            url.replace(&format!("{{{}}}", name), value)
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerVariable {
+    #[serde(default, rename = "enum")]
+    pub enumeration: Vec<String>,
+    pub default: String,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PathItem {
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub servers: Vec<Server>,
+    #[serde(default)]
+    pub parameters: Vec<RefOr<Parameter>>,
+    pub get: Option<Operation>,
+    pub put: Option<Operation>,
+    pub post: Option<Operation>,
+    pub delete: Option<Operation>,
+    pub options: Option<Operation>,
+    pub head: Option<Operation>,
+    pub patch: Option<Operation>,
+    pub trace: Option<Operation>,
+}
+
+impl PathItem {
+    /// Operations paired with their lowercase method, in the order of the specification.
+    pub fn operations(&self) -> impl Iterator<Item = (&'static str, &Operation)> {
+        [
+            ("get", &self.get),
+            ("put", &self.put),
+            ("post", &self.post),
+            ("delete", &self.delete),
+            ("options", &self.options),
+            ("head", &self.head),
+            ("patch", &self.patch),
+            ("trace", &self.trace),
+        ]
+        .into_iter()
+        .filter_map(|(method, operation)| operation.as_ref().map(|operation| (method, operation)))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    pub operation_id: Option<String>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub parameters: Vec<RefOr<Parameter>>,
+    pub request_body: Option<RefOr<RequestBody>>,
+    #[serde(default)]
+    pub responses: IndexMap<String, RefOr<Response>>,
+    pub security: Option<Vec<SecurityRequirement>>,
+    #[serde(default)]
+    pub servers: Vec<Server>,
+    #[serde(default)]
+    pub deprecated: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+    Cookie,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Parameter {
+    pub name: String,
+    #[serde(rename = "in")]
+    pub location: ParameterLocation,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub deprecated: bool,
+    pub style: Option<String>,
+    pub explode: Option<bool>,
+    #[serde(default)]
+    pub allow_reserved: bool,
+    #[serde(default, deserialize_with = "lenient")]
+    pub schema: Option<Schema>,
+    pub example: Option<Value>,
+    #[serde(default)]
+    pub examples: IndexMap<String, RefOr<Example>>,
+    #[serde(default)]
+    pub content: IndexMap<String, MediaType>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestBody {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub content: IndexMap<String, MediaType>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaType {
+    #[serde(default, deserialize_with = "lenient")]
+    pub schema: Option<Schema>,
+    pub example: Option<Value>,
+    #[serde(default)]
+    pub examples: IndexMap<String, RefOr<Example>>,
+    #[serde(default)]
+    pub encoding: IndexMap<String, Encoding>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Example {
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub value: Option<Value>,
+    pub external_value: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Encoding {
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub headers: IndexMap<String, RefOr<Header>>,
+    pub style: Option<String>,
+    pub explode: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Response {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub headers: IndexMap<String, RefOr<Header>>,
+    #[serde(default)]
+    pub content: IndexMap<String, MediaType>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Header {
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default, deserialize_with = "lenient")]
+    pub schema: Option<Schema>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Components {
+    #[serde(default, deserialize_with = "lenient_map")]
+    pub schemas: IndexMap<String, Schema>,
+    #[serde(default)]
+    pub responses: IndexMap<String, RefOr<Response>>,
+    #[serde(default)]
+    pub parameters: IndexMap<String, RefOr<Parameter>>,
+    #[serde(default)]
+    pub examples: IndexMap<String, RefOr<Example>>,
+    #[serde(default)]
+    pub request_bodies: IndexMap<String, RefOr<RequestBody>>,
+    #[serde(default)]
+    pub headers: IndexMap<String, RefOr<Header>>,
+    #[serde(default)]
+    pub security_schemes: IndexMap<String, SecurityScheme>,
+    #[serde(default)]
+    pub path_items: IndexMap<String, PathItem>,
+}
+
+/// A JSON schema as used by OpenAPI 3.0, or a JSON Schema 2020-12 schema as used by OpenAPI 3.1.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    #[serde(rename = "$ref")]
+    pub reference: Option<String>,
+    #[serde(rename = "type")]
+    pub schema_type: Option<SchemaType>,
+    pub format: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub default: Option<Value>,
+    pub example: Option<Value>,
+    /// The 3.1 replacement of `example`; the values of a map are taken as well.
+    #[serde(default, deserialize_with = "schema_examples")]
+    pub examples: Option<Vec<Value>>,
+    #[serde(rename = "enum")]
+    pub enumeration: Option<Vec<Value>>,
+    #[serde(rename = "const")]
+    pub const_value: Option<Value>,
+    /// 3.0 only; 3.1 lists `null` among the types instead.
+    #[serde(default)]
+    pub nullable: bool,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub write_only: bool,
+    #[serde(default, deserialize_with = "lenient_map")]
+    pub properties: IndexMap<String, Schema>,
+    #[serde(default)]
+    pub required: Vec<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub additional_properties: Option<AdditionalProperties>,
+    #[serde(default, deserialize_with = "bool_or_schema")]
+    pub items: Option<Box<Schema>>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub prefix_items: Vec<Schema>,
+    #[serde(default, rename = "$defs", deserialize_with = "lenient_map")]
+    pub defs: IndexMap<String, Schema>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub all_of: Vec<Schema>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub one_of: Vec<Schema>,
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub any_of: Vec<Schema>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub not: Option<Box<Schema>>,
+    pub multiple_of: Option<f64>,
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    pub exclusive_minimum: Option<ExclusiveBound>,
+    pub exclusive_maximum: Option<ExclusiveBound>,
+    pub min_length: Option<u64>,
+    pub max_length: Option<u64>,
+    pub pattern: Option<String>,
+    pub min_items: Option<u64>,
+    pub max_items: Option<u64>,
+    #[serde(default)]
+    pub unique_items: bool,
+    pub min_properties: Option<u64>,
+    pub max_properties: Option<u64>,
+}
+
+impl Schema {
+    /// The types the schema allows, without `null`.
+    pub fn types(&self) -> Vec<&str> {
+        match &self.schema_type {
+            Some(SchemaType::Single(schema_type)) => vec![schema_type.as_str()],
+            Some(SchemaType::Multiple(types)) => types.iter().map(String::as_str).collect(),
+            None => Vec::new(),
+        }
+        .into_iter()
+        .filter(|schema_type| *schema_type != "null")
+        .collect()
+    }
+
+    /// The first non-null type, if the schema declares one.
+    pub fn primary_type(&self) -> Option<&str> {
+        self.types().first().copied()
+    }
+
+    /// Whether `null` is allowed, through `nullable` (3.0) or a `null` type (3.1).
+    pub fn is_nullable(&self) -> bool {
+        self.nullable
+            || match &self.schema_type {
+                Some(SchemaType::Single(schema_type)) => schema_type == "null",
+                Some(SchemaType::Multiple(types)) => types.iter().any(|schema_type| schema_type == "null"),
+                None => false,
+            }
+    }
+
+    /// The lower bound and whether it is exclusive, in either the 3.0 or the 3.1 form.
+    pub fn lower_bound(&self) -> Option<(f64, bool)> {
+        bound(self.minimum, &self.exclusive_minimum)
+    }
+
+    /// The upper bound and whether it is exclusive, in either the 3.0 or the 3.1 form.
+    pub fn upper_bound(&self) -> Option<(f64, bool)> {
+        bound(self.maximum, &self.exclusive_maximum)
+    }
+}
+
+fn bound(inclusive: Option<f64>, exclusive: &Option<ExclusiveBound>) -> Option<(f64, bool)> {
+    match (inclusive, exclusive) {
+        (_, Some(ExclusiveBound::Value(value))) => Some((*value, true)),
+        (Some(value), Some(ExclusiveBound::Flag(exclusive))) => Some((value, *exclusive)),
+        (Some(value), None) => Some((value, false)),
+        (None, _) => None,
+    }
+}
+
+/// `type` is a single name, or in 3.1 a list of names.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SchemaType {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+/// `exclusiveMinimum`/`exclusiveMaximum` modify `minimum`/`maximum` in 3.0 and are bounds of their own in 3.1.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum ExclusiveBound {
+    Flag(bool),
+    Value(f64),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BoolOrSchema {
+    Bool(bool),
+    Schema(Box<Schema>),
+}
+
+/// JSON Schema allows `true` and `false` as schemas; they are read as `{}` and `{ not: {} }`.
+fn bool_or_schema<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Box<Schema>>, D::Error> {
+    Ok(match lenient(deserializer)? {
+        Some(BoolOrSchema::Bool(true)) => Some(Box::default()),
+        Some(BoolOrSchema::Bool(false)) => Some(Box::new(Schema {
+            not: Some(Box::default()),
+            ..Default::default()
+        })),
+        Some(BoolOrSchema::Schema(schema)) => Some(schema),
+        None => None,
+    })
+}
+
+/// Schemas are read leniently: a node that doesn't parse is dropped where it appears instead of
+/// rejecting the whole document, which would disable every console.
+fn lenient<'de, D: Deserializer<'de>, T: DeserializeOwned>(deserializer: D) -> Result<Option<T>, D::Error> {
+    Ok(serde_json::from_value(Value::deserialize(deserializer)?).ok())
+}
+
+fn lenient_vec<'de, D: Deserializer<'de>, T: DeserializeOwned>(deserializer: D) -> Result<Vec<T>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Array(items) => items.into_iter().filter_map(|item| serde_json::from_value(item).ok()).collect(),
+        _ => Vec::new(),
+    })
+}
+
+fn lenient_map<'de, D: Deserializer<'de>, T: DeserializeOwned>(
+    deserializer: D,
+) -> Result<IndexMap<String, T>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Object(entries) => entries
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, serde_json::from_value(value).ok()?)))
+            .collect(),
+        _ => IndexMap::new(),
+    })
+}
+
+/// `examples` is a list in JSON Schema, but is often written as a map like the OpenAPI `examples`.
+fn schema_examples<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<Value>>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Array(items) => Some(items),
+        Value::Object(entries) => Some(entries.into_iter().map(|(_, value)| value).collect()),
+        _ => None,
+    })
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum AdditionalProperties {
+    Allowed(bool),
+    Schema(Box<Schema>),
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiKeyLocation {
+    Header,
+    Query,
+    Cookie,
+}
+
+/// An entry of `components.securitySchemes`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SecurityScheme {
+    #[serde(rename_all = "camelCase")]
+    ApiKey {
+        name: String,
+        #[serde(rename = "in")]
+        location: ApiKeyLocation,
+        description: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Http {
+        scheme: String,
+        bearer_format: Option<String>,
+        description: Option<String>,
+    },
+    #[serde(rename = "oauth2", rename_all = "camelCase")]
+    OAuth2 {
+        flows: Box<OAuthFlows>,
+        description: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    OpenIdConnect {
+        open_id_connect_url: String,
+        description: Option<String>,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+impl SecurityScheme {
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            SecurityScheme::ApiKey { description, .. }
+            | SecurityScheme::Http { description, .. }
+            | SecurityScheme::OAuth2 { description, .. }
+            | SecurityScheme::OpenIdConnect { description, .. } => description.as_deref(),
+            SecurityScheme::Unsupported => None,
+        }
+    }
+}
+
+/// The `flows` object of an oauth2 security scheme.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthFlows {
+    pub implicit: Option<OAuthFlow>,
+    pub password: Option<OAuthFlow>,
+    pub client_credentials: Option<OAuthFlow>,
+    pub authorization_code: Option<OAuthFlow>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthFlow {
+    pub authorization_url: Option<String>,
+    pub token_url: Option<String>,
+    pub refresh_url: Option<String>,
+    #[serde(default)]
+    pub scopes: BTreeMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+    use crate::openapi::loader::parse_document;
+
+    const OPENAPI_31: &str = r###"
+openapi: 3.1.0
+jsonSchemaDialect: https://json-schema.org/draft/2020-12/schema
+info:
+  title: Events
+  version: 1.0.0
+webhooks:
+  newPet:
+    post:
+      operationId: newPetHook
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+components:
+  schemas:
+    Pet:
+      type: object
+      $defs:
+        Kind:
+          const: dog
+      properties:
+        name:
+          type: [string, 'null']
+        kind:
+          $ref: '#/components/schemas/Pet/$defs/Kind'
+        age:
+          type: integer
+          exclusiveMinimum: 0
+          maximum: 30
+        position:
+          type: array
+          prefixItems:
+            - type: number
+            - type: number
+          items: false
+        tags:
+          type: array
+          items: true
+          examples:
+            - [good, boy]
+    Legacy:
+      type: number
+      nullable: true
+      minimum: 1
+      exclusiveMinimum: true
+"###;
+
+    fn api() -> OpenApi {
+        OpenApi::deserialize(parse_document(OPENAPI_31).unwrap()).unwrap()
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_openapi_31_document() {
+        let api = api();
+
+        assert_eq!(Some("https://json-schema.org/draft/2020-12/schema"), api.json_schema_dialect.as_deref());
+        assert!(api.paths.is_empty());
+        let hook = api.webhooks["newPet"].post.as_ref().unwrap();
+        assert_eq!(Some("newPetHook"), hook.operation_id.as_deref());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_type_lists_and_const() {
+        let pet = &api().components.schemas["Pet"];
+
+        let name = &pet.properties["name"];
+        assert_eq!(Some(SchemaType::Multiple(vec!["string".to_string(), "null".to_string()])), name.schema_type);
+        assert_eq!(vec!["string"], name.types());
+        assert!(name.is_nullable());
+        assert!(!pet.is_nullable());
+
+        assert_eq!(Some(Value::from("dog")), pet.defs["Kind"].const_value);
+        assert_eq!(Some(vec![serde_json::json!(["good", "boy"])]), pet.properties["tags"].examples);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_prefix_items_and_boolean_schemas() {
+        let pet = &api().components.schemas["Pet"];
+
+        let position = &pet.properties["position"];
+        assert_eq!(2, position.prefix_items.len());
+        assert_eq!(Some("number"), position.prefix_items[0].primary_type());
+        // `items: false` forbids anything past the prefix
+        assert_eq!(Some(Box::default()), position.items.as_ref().unwrap().not);
+        assert_eq!(Some(Box::default()), pet.properties["tags"].items);
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_exclusive_bounds() {
+        let api = api();
+        let age = &api.components.schemas["Pet"].properties["age"];
+        let legacy = &api.components.schemas["Legacy"];
+
+        assert_eq!(Some((0.0, true)), age.lower_bound());
+        assert_eq!(Some((30.0, false)), age.upper_bound());
+        assert_eq!(Some((1.0, true)), legacy.lower_bound());
+        assert!(legacy.is_nullable());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_malformed_schemas_are_dropped() {
+        let document = parse_document(
+            r###"
+openapi: 3.1.0
+paths: {}
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+          examples:
+            short: Rex
+            long: Rexford
+        age:
+          type: integer
+          minimum: young
+    Broken: 42
+"###,
+        )
+        .unwrap();
+        let api = OpenApi::deserialize(document).unwrap();
+        let pet = &api.components.schemas["Pet"];
+
+        assert_eq!(Some(vec![Value::from("Rex"), Value::from("Rexford")]), pet.properties["name"].examples);
+        assert!(!pet.properties.contains_key("age"));
+        assert!(!api.components.schemas.contains_key("Broken"));
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use serde_json::{Map, Value};
+use url::Url;
+use wasm_bindgen::prelude::*;
+use super::loader::fetch_document;
+
+/// Resolve `reference` against `base`, leaving it untouched when `base` is not absolute.
+pub fn resolve_url(base: &str, reference: &str) -> String {
+    Url::parse(base)
+        .and_then(|base| base.join(reference))
+        .map(String::from)
+        .unwrap_or_else(|_| reference.to_string())
+}
+
+/// Fetch every document referenced from `document`, directly or through other
+/// referenced documents, and inline them so that only local refs remain.
+pub async fn bundle(url: &str, document: Value) -> Result<Value, JsValue> {
+    let mut documents = BTreeMap::new();
+    let mut pending = external_urls(url, &document);
+    while let Some(next) = pending.pop() {
+        if next == url || documents.contains_key(&next) {
+            continue;
+        }
+        let external = fetch_document(&next).await?;
+        pending.extend(external_urls(&next, &external));
+        documents.insert(next, external);
+    }
+    Ok(bundle_documents(url, document, &documents))
+}
+
+/// Rewrite the refs of `document` into local refs, given every document it references.
+///
+/// A referenced value is copied to where it is first referenced from and later
+/// refs to it point there, so circular references stay refs.
+pub fn bundle_documents(url: &str, mut document: Value, documents: &BTreeMap<String, Value>) -> Value {
+    let mut bundler = Bundler {
+        root_url: url,
+        documents,
+        inlined: BTreeMap::new(),
+    };
+    bundler.rewrite(&mut document, url, String::new());
+    document
+}
+
+struct Bundler<'a> {
+    root_url: &'a str,
+    documents: &'a BTreeMap<String, Value>,
+    /// `url#pointer` of inlined values mapped to where they were inlined.
+    inlined: BTreeMap<String, String>,
+}
+
+impl Bundler<'_> {
+    fn rewrite(&mut self, value: &mut Value, base_url: &str, location: String) {
+        let reference = value.get("$ref").and_then(Value::as_str).map(str::to_string);
+        if let Some(reference) = reference {
+            let (file, pointer) = reference.split_once('#').unwrap_or((&reference, ""));
+            let target_url = if file.is_empty() {
+                base_url.to_string()
+            } else {
+                resolve_url(base_url, file)
+            };
+            if target_url == self.root_url {
+                if !file.is_empty() {
+                    *value = local_ref(pointer);
+                }
+                return;
+            }
+
+            let key = format!("{}#{}", target_url, pointer);
+            if let Some(inlined) = self.inlined.get(&key) {
+                *value = local_ref(inlined);
+                return;
+            }
+            let Some(target) = self.documents.get(&target_url).and_then(|document| document.pointer(pointer)) else {
+                return;
+            };
+            self.inlined.insert(key, location.clone());
+            *value = target.clone();
+            self.rewrite(value, &target_url, location);
+            return;
+        }
+
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    self.rewrite(child, base_url, format!("{}/{}", location, escape_pointer_token(key)));
+                }
+            }
+            Value::Array(items) => {
+                for (index, child) in items.iter_mut().enumerate() {
+                    self.rewrite(child, base_url, format!("{}/{}", location, index));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn local_ref(pointer: &str) -> Value {
+    let mut map = Map::new();
+    map.insert("$ref".to_string(), Value::String(format!("#{}", pointer)));
+    Value::Object(map)
+}
+
+/// Urls of the other documents `document` refers to.
+fn external_urls(url: &str, document: &Value) -> Vec<String> {
+    let mut urls = Vec::new();
+    collect_external_urls(url, document, &mut urls);
+    urls
+}
+
+fn collect_external_urls(url: &str, value: &Value, urls: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get("$ref") {
+                let file = reference.split('#').next().unwrap_or_default();
+                if !file.is_empty() {
+                    urls.push(resolve_url(url, file));
+                }
+            }
+            for child in map.values() {
+                collect_external_urls(url, child, urls);
+            }
+        }
+        Value::Array(items) => {
+            for child in items {
+                collect_external_urls(url, child, urls);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Escape a single JSON pointer token (RFC 6901).
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_resolve_url() {
+        assert_eq!(
+            "https://example.com/api/schemas/user.yaml",
+            resolve_url("https://example.com/api/openapi.yaml", "./schemas/user.yaml")
+        );
+        assert_eq!(
+            "https://other.example.com/pet.json",
+            resolve_url("https://example.com/api/openapi.yaml", "https://other.example.com/pet.json")
+        );
+        assert_eq!("user.yaml", resolve_url("openapi.yaml", "user.yaml"));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_external_urls() {
+        let document = json!({
+            "a": { "$ref": "./schemas/user.yaml#/User" },
+            "b": [{ "$ref": "https://example.com/pet.json" }],
+            "c": { "$ref": "#/components/schemas/Local" }
+        });
+
+        assert_eq!(
+            vec![
+                "https://example.com/api/schemas/user.yaml".to_string(),
+                "https://example.com/pet.json".to_string(),
+            ],
+            external_urls("https://example.com/api/openapi.yaml", &document)
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_bundle_documents() {
+        let root = "https://example.com/api/openapi.yaml";
+        let document = json!({
+            "components": {
+                "schemas": {
+                    "User": { "$ref": "./schemas/user.yaml#/User" },
+                    "Users": { "type": "array", "items": { "$ref": "schemas/user.yaml#/User" } },
+                    "Local": { "$ref": "#/components/schemas/User" }
+                }
+            }
+        });
+        let documents = BTreeMap::from([(
+            "https://example.com/api/schemas/user.yaml".to_string(),
+            json!({
+                "User": {
+                    "type": "object",
+                    "properties": {
+                        "friends": { "type": "array", "items": { "$ref": "#/User" } },
+                        "address": { "$ref": "#/Address" },
+                        "home": { "$ref": "../openapi.yaml#/components/schemas/Local" }
+                    }
+                },
+                "Address": { "type": "string" }
+            }),
+        )]);
+
+        assert_eq!(
+            json!({
+                "components": {
+                    "schemas": {
+                        "User": {
+                            "type": "object",
+                            "properties": {
+                                "friends": { "type": "array", "items": { "$ref": "#/components/schemas/User" } },
+                                "address": { "type": "string" },
+                                "home": { "$ref": "#/components/schemas/Local" }
+                            }
+                        },
+                        "Users": { "type": "array", "items": { "$ref": "#/components/schemas/User" } },
+                        "Local": { "$ref": "#/components/schemas/User" }
+                    }
+                }
+            }),
+            bundle_documents(root, document, &documents)
+        );
+    }
+}
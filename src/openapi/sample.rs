@@ -0,0 +1,372 @@
+use serde_json::{Map, Value};
+use super::{RequestBody, Schema, SchemaType, Spec};
+
+const DEFAULT_MAX_DEPTH: u32 = 10;
+
+/// How request samples are generated, taken from the matching Redoc options.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleOptions {
+    pub max_depth: u32,
+    pub only_required: bool,
+}
+
+impl Default for SampleOptions {
+    fn default() -> SampleOptions {
+        SampleOptions {
+            max_depth: DEFAULT_MAX_DEPTH,
+            only_required: false,
+        }
+    }
+}
+
+/// A value the request body editor can be filled with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyExample {
+    pub label: String,
+    pub text: String,
+}
+
+/// The media type a request body is sent as and the examples offered for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyExamples {
+    pub media_type: String,
+    pub examples: Vec<BodyExample>,
+}
+
+/// The named examples of the preferred media type of `body`, followed by a
+/// sample generated from its schema.
+pub fn body_examples(spec: &Spec, body: &RequestBody, options: SampleOptions) -> Option<BodyExamples> {
+    let (media_type, media) = body
+        .content
+        .iter()
+        .find(|(media_type, _)| is_json(media_type))
+        .or_else(|| body.content.first())?;
+
+    let mut examples: Vec<BodyExample> = media
+        .examples
+        .iter()
+        .filter_map(|(name, example)| {
+            let example = spec.resolve(example)?;
+            let value = example.value.as_ref()?;
+            Some(BodyExample {
+                label: example.summary.clone().unwrap_or_else(|| name.clone()),
+                text: to_text(media_type, value),
+            })
+        })
+        .collect();
+    let generated = match (&media.example, &media.schema) {
+        (Some(example), _) => Some(example.clone()),
+        (None, Some(schema)) => Some(request_sample(spec, schema, options)),
+        (None, None) => None,
+    };
+    if let Some(generated) = generated {
+        examples.push(BodyExample {
+            label: "Generated sample".to_string(),
+            text: to_text(media_type, &generated),
+        });
+    }
+
+    Some(BodyExamples {
+        media_type: media_type.clone(),
+        examples,
+    })
+}
+
+pub fn is_json(media_type: &str) -> bool {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+fn to_text(media_type: &str, value: &Value) -> String {
+    match value {
+        Value::String(text) if !is_json(media_type) => text.clone(),
+        _ => serde_json::to_string_pretty(value).unwrap_or_default(),
+    }
+}
+
+/// A value a client could send for `schema`; read only properties are left out.
+pub fn request_sample(spec: &Spec, schema: &Schema, options: SampleOptions) -> Value {
+    Sampler { spec, options }.sample(schema, 0)
+}
+
+struct Sampler<'a> {
+    spec: &'a Spec,
+    options: SampleOptions,
+}
+
+impl Sampler<'_> {
+    fn sample(&self, schema: &Schema, depth: u32) -> Value {
+        let schema = self.spec.resolve_schema(schema);
+        let explicit = schema
+            .const_value
+            .as_ref()
+            .or(schema.example.as_ref())
+            .or_else(|| schema.examples.as_ref().and_then(|examples| examples.first()))
+            .or(schema.default.as_ref())
+            .or_else(|| schema.enumeration.as_ref().and_then(|values| values.first()));
+        if let Some(value) = explicit {
+            return value.clone();
+        }
+
+        if !schema.all_of.is_empty() {
+            let mut merged = Value::Null;
+            for member in &schema.all_of {
+                merge(&mut merged, self.sample(member, depth));
+            }
+            if !schema.properties.is_empty() || schema.primary_type() == Some("object") {
+                let own = Schema {
+                    all_of: Vec::new(),
+                    ..schema.as_ref().clone()
+                };
+                merge(&mut merged, self.sample(&own, depth));
+            }
+            return merged;
+        }
+        if let Some(first) = schema.one_of.first().or_else(|| schema.any_of.first()) {
+            return self.sample(first, depth);
+        }
+
+        match schema_type(&schema) {
+            Some("object") => self.object(&schema, depth),
+            Some("array") => self.array(&schema, depth),
+            Some("string") => Value::from(string_sample(schema.format.as_deref())),
+            Some("integer") => Value::from(number_sample(&schema).ceil() as i64),
+            Some("number") => Value::from(number_sample(&schema)),
+            Some("boolean") => Value::Bool(true),
+            _ => Value::Null,
+        }
+    }
+
+    fn object(&self, schema: &Schema, depth: u32) -> Value {
+        let mut object = Map::new();
+        if depth >= self.options.max_depth {
+            return Value::Object(object);
+        }
+        for (name, property) in &schema.properties {
+            let required = schema.required.contains(name);
+            if self.options.only_required && !required {
+                continue;
+            }
+            if self.spec.resolve_schema(property).read_only {
+                continue;
+            }
+            object.insert(name.clone(), self.sample(property, depth + 1));
+        }
+        Value::Object(object)
+    }
+
+    fn array(&self, schema: &Schema, depth: u32) -> Value {
+        if depth >= self.options.max_depth {
+            return Value::Array(Vec::new());
+        }
+        if !schema.prefix_items.is_empty() {
+            return Value::Array(
+                schema
+                    .prefix_items
+                    .iter()
+                    .map(|item| self.sample(item, depth + 1))
+                    .collect(),
+            );
+        }
+        match &schema.items {
+            // `items: false` is read as `{ not: {} }`, nothing may be added
+            Some(items) if items.not.is_some() => Value::Array(Vec::new()),
+            Some(items) => Value::Array(vec![self.sample(items, depth + 1)]),
+            None => Value::Array(Vec::new()),
+        }
+    }
+}
+
+/// The declared type, or the one implied by the keywords used.
+fn schema_type(schema: &Schema) -> Option<&str> {
+    if let Some(schema_type) = schema.primary_type() {
+        return Some(schema_type);
+    }
+    if matches!(&schema.schema_type, Some(SchemaType::Single(null)) if null == "null") {
+        return Some("null");
+    }
+    if !schema.properties.is_empty() || schema.additional_properties.is_some() {
+        Some("object")
+    } else if schema.items.is_some() || !schema.prefix_items.is_empty() {
+        Some("array")
+    } else {
+        None
+    }
+}
+
+fn merge(target: &mut Value, value: Value) {
+    match (target, value) {
+        (Value::Object(target), Value::Object(value)) => target.extend(value),
+        (target, value) => *target = value,
+    }
+}
+
+fn string_sample(format: Option<&str>) -> &'static str {
+    match format {
+        Some("date-time") => "2024-01-01T12:00:00Z",
+        Some("date") => "2024-01-01",
+        Some("time") => "12:00:00Z",
+        Some("email") => "user@example.com",
+        Some("uuid") => "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+        Some("uri") | Some("url") => "https://example.com",
+        Some("hostname") => "example.com",
+        Some("ipv4") => "192.0.2.1",
+        Some("ipv6") => "2001:db8::1",
+        Some("byte") => "c3RyaW5n",
+        Some("password") => "********",
+        Some("binary") => "",
+        _ => "string",
+    }
+}
+
+fn number_sample(schema: &Schema) -> f64 {
+    match (schema.lower_bound(), schema.upper_bound()) {
+        (Some((minimum, false)), _) => minimum,
+        (Some((minimum, true)), _) if schema.primary_type() == Some("integer") => minimum.floor() + 1.0,
+        (Some((minimum, true)), _) => minimum + 1.0,
+        (None, Some((maximum, exclusive))) if maximum <= 0.0 => {
+            if exclusive {
+                maximum - 1.0
+            } else {
+                maximum
+            }
+        }
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn spec() -> Spec {
+        Spec::new(
+            "https://example.com/openapi.json",
+            json!({
+                "openapi": "3.1.0",
+                "paths": {
+                    "/pet": {
+                        "post": {
+                            "operationId": "addPet",
+                            "requestBody": {
+                                "content": {
+                                    "application/xml": { "schema": { "$ref": "#/components/schemas/Pet" } },
+                                    "application/json": {
+                                        "schema": { "$ref": "#/components/schemas/Pet" },
+                                        "examples": {
+                                            "cat": { "summary": "A cat", "value": { "name": "Tom" } },
+                                            "dog": { "$ref": "#/components/examples/Dog" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "components": {
+                    "examples": { "Dog": { "value": { "name": "Rex" } } },
+                    "schemas": {
+                        "Pet": {
+                            "type": "object",
+                            "required": ["name"],
+                            "properties": {
+                                "id": { "type": "integer", "readOnly": true },
+                                "name": { "type": "string", "example": "doggie" },
+                                "status": { "type": "string", "enum": ["available", "sold"] },
+                                "kind": { "const": "pet" },
+                                "born": { "type": "string", "format": "date" },
+                                "weight": { "type": ["number", "null"], "exclusiveMinimum": 0 },
+                                "owner": { "$ref": "#/components/schemas/Owner" },
+                                "tags": { "type": "array", "items": { "type": "string", "default": "cute" } },
+                                "position": { "type": "array", "prefixItems": [{ "type": "integer" }, { "type": "boolean" }] },
+                                "extra": { "allOf": [{ "$ref": "#/components/schemas/Base" }, { "properties": { "more": { "type": "boolean" } } }] }
+                            }
+                        },
+                        "Base": { "type": "object", "properties": { "base": { "type": "string" } } },
+                        "Owner": {
+                            "type": "object",
+                            "properties": { "pets": { "type": "array", "items": { "$ref": "#/components/schemas/Owner" } } }
+                        }
+                    }
+                }
+            }),
+        )
+        .unwrap()
+    }
+
+    fn pet() -> Schema {
+        Schema {
+            reference: Some("#/components/schemas/Pet".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_request_sample() {
+        let spec = spec();
+        let options = SampleOptions {
+            max_depth: 3,
+            only_required: false,
+        };
+
+        assert_eq!(
+            json!({
+                "name": "doggie",
+                "status": "available",
+                "kind": "pet",
+                "born": "2024-01-01",
+                "weight": 1.0,
+                "owner": { "pets": [{}] },
+                "tags": ["cute"],
+                "position": [0, true],
+                "extra": { "base": "string", "more": true }
+            }),
+            request_sample(&spec, &pet(), options)
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_only_required() {
+        let spec = spec();
+        let options = SampleOptions {
+            max_depth: 10,
+            only_required: true,
+        };
+
+        assert_eq!(json!({ "name": "doggie" }), request_sample(&spec, &pet(), options));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_body_examples() {
+        let spec = spec();
+        let operation = spec.find_operation_by_id("addPet").unwrap();
+        let body = operation.request_body(&spec).unwrap();
+        let options = SampleOptions {
+            max_depth: 1,
+            only_required: true,
+        };
+
+        let examples = body_examples(&spec, &body, options).unwrap();
+        assert_eq!("application/json", examples.media_type);
+        assert_eq!(
+            vec![
+                BodyExample {
+                    label: "A cat".to_string(),
+                    text: "{\n  \"name\": \"Tom\"\n}".to_string(),
+                },
+                BodyExample {
+                    label: "dog".to_string(),
+                    text: "{\n  \"name\": \"Rex\"\n}".to_string(),
+                },
+                BodyExample {
+                    label: "Generated sample".to_string(),
+                    text: "{\n  \"name\": \"doggie\"\n}".to_string(),
+                },
+            ],
+            examples.examples
+        );
+    }
+}
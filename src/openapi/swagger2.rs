@@ -0,0 +1,736 @@
+//! Upgrade of Swagger 2.0 documents to OpenAPI 3.0, following the mapping of
+//! the OpenAPI 3.0 migration guide.
+
+use std::collections::BTreeSet;
+use serde_json::{json, Map, Value};
+use url::Url;
+
+const PARAMETER_SCHEMA_FIELDS: [&str; 15] = [
+    "type",
+    "format",
+    "items",
+    "enum",
+    "default",
+    "maximum",
+    "exclusiveMaximum",
+    "minimum",
+    "exclusiveMinimum",
+    "maxLength",
+    "minLength",
+    "pattern",
+    "maxItems",
+    "minItems",
+    "uniqueItems",
+];
+const DEFAULT_MEDIA_TYPE: &str = "application/json";
+const URLENCODED: &str = "application/x-www-form-urlencoded";
+const MULTIPART: &str = "multipart/form-data";
+
+pub fn is_swagger2(document: &Value) -> bool {
+    document.get("swagger").and_then(Value::as_str) == Some("2.0")
+}
+
+/// Convert the Swagger 2.0 `document` loaded from `url` into an OpenAPI 3.0 document.
+pub fn upgrade(url: &str, document: &Value) -> Value {
+    let upgrade = Upgrade::new(document);
+    let mut upgraded = Map::new();
+    upgraded.insert("openapi".to_string(), json!("3.0.3"));
+    for field in ["info", "tags", "externalDocs", "security"] {
+        if let Some(value) = document.get(field) {
+            upgraded.insert(field.to_string(), value.clone());
+        }
+    }
+    upgraded.insert("servers".to_string(), Value::Array(servers(url, document)));
+
+    let mut paths = Map::new();
+    for (path, path_item) in object(document.get("paths")) {
+        paths.insert(path.clone(), upgrade.path_item(path_item));
+    }
+    upgraded.insert("paths".to_string(), Value::Object(paths));
+    upgraded.insert("components".to_string(), upgrade.components());
+
+    let mut upgraded = Value::Object(upgraded);
+    upgrade.rewrite_refs(&mut upgraded);
+    upgraded
+}
+
+fn servers(url: &str, document: &Value) -> Vec<Value> {
+    let base_path = document.get("basePath").and_then(Value::as_str).unwrap_or("");
+    let spec_url = Url::parse(url).ok();
+    let host = document
+        .get("host")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| {
+            let spec_url = spec_url.as_ref()?;
+            let host = spec_url.host_str()?;
+            Some(match spec_url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_string(),
+            })
+        });
+    let Some(host) = host else {
+        return vec![json!({ "url": if base_path.is_empty() { "/" } else { base_path } })];
+    };
+
+    let mut schemes: Vec<&str> = array(document.get("schemes")).iter().filter_map(Value::as_str).collect();
+    if schemes.is_empty() {
+        schemes.push(spec_url.as_ref().map(Url::scheme).unwrap_or("https"));
+    }
+    schemes
+        .into_iter()
+        .map(|scheme| json!({ "url": format!("{}://{}{}", scheme, host, base_path) }))
+        .collect()
+}
+
+struct Upgrade<'a> {
+    document: &'a Value,
+    /// Names of the global parameters that are `in: body`; refs to them become request body refs.
+    body_parameters: BTreeSet<String>,
+    /// Names of the global parameters that are `in: formData`; refs to them are resolved into form bodies.
+    form_parameters: BTreeSet<String>,
+}
+
+impl<'a> Upgrade<'a> {
+    fn new(document: &'a Value) -> Upgrade<'a> {
+        let names = |in_: &str| {
+            object(document.get("parameters"))
+                .iter()
+                .filter(|(_, parameter)| location(parameter) == Some(in_))
+                .map(|(name, _)| name.clone())
+                .collect()
+        };
+        Upgrade {
+            document,
+            body_parameters: names("body"),
+            form_parameters: names("formData"),
+        }
+    }
+
+    fn components(&self) -> Value {
+        let mut parameters = Map::new();
+        let mut request_bodies = Map::new();
+        for (name, parameter) in object(self.document.get("parameters")) {
+            if self.body_parameters.contains(name) {
+                let consumes = self.media_types("consumes", None);
+                request_bodies.insert(name.clone(), body_request_body(parameter, &consumes));
+            } else if !self.form_parameters.contains(name) {
+                parameters.insert(name.clone(), upgrade_parameter(parameter));
+            }
+        }
+        let produces = self.media_types("produces", None);
+        let responses: Map<String, Value> = object(self.document.get("responses"))
+            .iter()
+            .map(|(name, response)| (name.clone(), upgrade_response(response, &produces)))
+            .collect();
+        let security_schemes: Map<String, Value> = object(self.document.get("securityDefinitions"))
+            .iter()
+            .map(|(name, scheme)| (name.clone(), upgrade_security_scheme(scheme)))
+            .collect();
+
+        json!({
+            "schemas": self.document.get("definitions").cloned().unwrap_or_else(|| json!({})),
+            "parameters": parameters,
+            "requestBodies": request_bodies,
+            "responses": responses,
+            "securitySchemes": security_schemes,
+        })
+    }
+
+    /// `consumes` or `produces` of `operation`, falling back to the global ones.
+    fn media_types(&self, field: &str, operation: Option<&Value>) -> Vec<String> {
+        let types: Vec<String> = operation
+            .and_then(|operation| operation.get(field))
+            .or_else(|| self.document.get(field))
+            .map(|types| array(Some(types)).iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default();
+        if types.is_empty() {
+            vec![DEFAULT_MEDIA_TYPE.to_string()]
+        } else {
+            types
+        }
+    }
+
+    fn path_item(&self, path_item: &Value) -> Value {
+        let mut upgraded = Map::new();
+        let shared = array(path_item.get("parameters"));
+        for (key, value) in object(Some(path_item)) {
+            match key.as_str() {
+                "parameters" => {
+                    // body and formData parameters move into the request body of each operation
+                    let parameters: Vec<Value> = shared
+                        .iter()
+                        .filter(|parameter| !self.is_body(parameter) && self.form_parameter(parameter).is_none())
+                        .map(upgrade_parameter)
+                        .collect();
+                    upgraded.insert(key.clone(), Value::Array(parameters));
+                }
+                "get" | "put" | "post" | "delete" | "options" | "head" | "patch" => {
+                    upgraded.insert(key.clone(), self.operation(value, shared));
+                }
+                _ => {
+                    upgraded.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        Value::Object(upgraded)
+    }
+
+    fn is_body(&self, parameter: &Value) -> bool {
+        location(parameter) == Some("body") || self.body_parameter_name(parameter).is_some()
+    }
+
+    fn body_parameter_name<'v>(&self, parameter: &'v Value) -> Option<&'v str> {
+        let name = parameter.get("$ref")?.as_str()?.strip_prefix("#/parameters/")?;
+        self.body_parameters.contains(name).then_some(name)
+    }
+
+    /// `parameter` itself if it is `in: formData`, or the global formData parameter it refers to.
+    fn form_parameter<'v>(&self, parameter: &'v Value) -> Option<&'v Value>
+    where
+        'a: 'v,
+    {
+        if location(parameter) == Some("formData") {
+            return Some(parameter);
+        }
+        let name = parameter.get("$ref")?.as_str()?.strip_prefix("#/parameters/")?;
+        if !self.form_parameters.contains(name) {
+            return None;
+        }
+        self.document.get("parameters")?.get(name)
+    }
+
+    fn operation(&self, operation: &Value, shared: &[Value]) -> Value {
+        let mut upgraded = Map::new();
+        let own = array(operation.get("parameters"));
+        // operation parameters override path parameters with the same name and location
+        let overridden = |parameter: &Value| {
+            own.iter().any(|other| {
+                other.get("name").is_some()
+                    && other.get("name") == parameter.get("name")
+                    && location(other) == location(parameter)
+            })
+        };
+        let parameters: Vec<&Value> = shared
+            .iter()
+            .filter(|parameter| !overridden(parameter))
+            .chain(own.iter())
+            .collect();
+
+        let consumes = self.media_types("consumes", Some(operation));
+        let mut request_body = None;
+        let mut form = Vec::new();
+        let mut upgraded_parameters = Vec::new();
+        for parameter in parameters {
+            if let Some(name) = self.body_parameter_name(parameter) {
+                request_body = Some(json!({ "$ref": format!("#/components/requestBodies/{}", name) }));
+            } else if location(parameter) == Some("body") {
+                request_body = Some(body_request_body(parameter, &consumes));
+            } else if let Some(parameter) = self.form_parameter(parameter) {
+                form.push(parameter);
+            } else if !shared.contains(parameter) {
+                upgraded_parameters.push(upgrade_parameter(parameter));
+            }
+        }
+        if request_body.is_none() && !form.is_empty() {
+            request_body = Some(form_request_body(&form, &consumes));
+        }
+
+        for (key, value) in object(Some(operation)) {
+            match key.as_str() {
+                "consumes" | "produces" | "schemes" => {}
+                "parameters" => {
+                    upgraded.insert(key.clone(), Value::Array(upgraded_parameters.clone()));
+                }
+                "responses" => {
+                    let produces = self.media_types("produces", Some(operation));
+                    let responses: Map<String, Value> = object(Some(value))
+                        .iter()
+                        .map(|(status, response)| (status.clone(), upgrade_response(response, &produces)))
+                        .collect();
+                    upgraded.insert(key.clone(), Value::Object(responses));
+                }
+                _ => {
+                    upgraded.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        if let Some(request_body) = request_body {
+            upgraded.insert("requestBody".to_string(), request_body);
+        }
+        Value::Object(upgraded)
+    }
+
+    /// Point refs at the locations their targets moved to.
+    fn rewrite_refs(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get_mut("$ref") {
+                    if let Some(rewritten) = self.rewrite_ref(reference) {
+                        *reference = rewritten;
+                    }
+                }
+                for child in map.values_mut() {
+                    self.rewrite_refs(child);
+                }
+            }
+            Value::Array(items) => {
+                for child in items {
+                    self.rewrite_refs(child);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn rewrite_ref(&self, reference: &str) -> Option<String> {
+        let (file, pointer) = reference.split_once('#')?;
+        let (section, rest) = pointer.strip_prefix('/')?.split_once('/')?;
+        let name = rest.split('/').next().unwrap_or(rest);
+        let target = match section {
+            "definitions" => "schemas",
+            "parameters" if file.is_empty() && self.body_parameters.contains(name) => "requestBodies",
+            "parameters" => "parameters",
+            "responses" => "responses",
+            _ => return None,
+        };
+        Some(format!("{}#/components/{}/{}", file, target, rest))
+    }
+}
+
+fn object(value: Option<&Value>) -> &Map<String, Value> {
+    static EMPTY: std::sync::OnceLock<Map<String, Value>> = std::sync::OnceLock::new();
+    value.and_then(Value::as_object).unwrap_or_else(|| EMPTY.get_or_init(Map::new))
+}
+
+fn array(value: Option<&Value>) -> &[Value] {
+    value.and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default()
+}
+
+fn location(parameter: &Value) -> Option<&str> {
+    parameter.get("in").and_then(Value::as_str)
+}
+
+/// A non-body parameter with its type information moved into `schema`.
+fn upgrade_parameter(parameter: &Value) -> Value {
+    if parameter.get("$ref").is_some() {
+        return parameter.clone();
+    }
+    let mut upgraded = Map::new();
+    for (key, value) in object(Some(parameter)) {
+        if !PARAMETER_SCHEMA_FIELDS.contains(&key.as_str()) && key != "collectionFormat" && key != "multipleOf" {
+            upgraded.insert(key.clone(), value.clone());
+        }
+    }
+    upgraded.insert("schema".to_string(), parameter_schema(parameter));
+
+    let collection_format = parameter.get("collectionFormat").and_then(Value::as_str);
+    if parameter.get("type").and_then(Value::as_str) == Some("array") {
+        let in_query = location(parameter) == Some("query");
+        let (style, explode) = match collection_format.unwrap_or("csv") {
+            "multi" => ("form", true),
+            "ssv" if in_query => ("spaceDelimited", false),
+            "pipes" if in_query => ("pipeDelimited", false),
+            _ if in_query || location(parameter) == Some("cookie") => ("form", false),
+            _ => ("simple", false),
+        };
+        upgraded.insert("style".to_string(), json!(style));
+        upgraded.insert("explode".to_string(), json!(explode));
+    }
+    Value::Object(upgraded)
+}
+
+fn parameter_schema(parameter: &Value) -> Value {
+    let mut schema = Map::new();
+    for field in PARAMETER_SCHEMA_FIELDS.iter().chain(["multipleOf"].iter()) {
+        if let Some(value) = parameter.get(*field) {
+            let value = match *field {
+                "items" => parameter_schema(value),
+                _ => value.clone(),
+            };
+            schema.insert(field.to_string(), value);
+        }
+    }
+    if schema.get("type").and_then(Value::as_str) == Some("file") {
+        schema.insert("type".to_string(), json!("string"));
+        schema.insert("format".to_string(), json!("binary"));
+    }
+    Value::Object(schema)
+}
+
+fn body_request_body(parameter: &Value, consumes: &[String]) -> Value {
+    let schema = parameter.get("schema").cloned().unwrap_or_else(|| json!({}));
+    let content: Map<String, Value> = consumes
+        .iter()
+        .map(|media_type| (media_type.clone(), json!({ "schema": schema })))
+        .collect();
+    let mut request_body = json!({
+        "content": content,
+        "required": parameter.get("required").and_then(Value::as_bool).unwrap_or(false),
+    });
+    if let Some(description) = parameter.get("description") {
+        request_body["description"] = description.clone();
+    }
+    request_body
+}
+
+fn form_request_body(parameters: &[&Value], consumes: &[String]) -> Value {
+    let has_file = parameters
+        .iter()
+        .any(|parameter| parameter.get("type").and_then(Value::as_str) == Some("file"));
+    let mut media_types: Vec<&str> = consumes
+        .iter()
+        .map(String::as_str)
+        .filter(|media_type| *media_type == URLENCODED || *media_type == MULTIPART)
+        .collect();
+    if media_types.is_empty() || has_file {
+        media_types = vec![if has_file { MULTIPART } else { URLENCODED }];
+    }
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for parameter in parameters {
+        let Some(name) = parameter.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let mut schema = parameter_schema(parameter);
+        if let Some(description) = parameter.get("description") {
+            schema["description"] = description.clone();
+        }
+        properties.insert(name.to_string(), schema);
+        if parameter.get("required").and_then(Value::as_bool).unwrap_or(false) {
+            required.push(json!(name));
+        }
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+
+    let content: Map<String, Value> = media_types
+        .into_iter()
+        .map(|media_type| (media_type.to_string(), json!({ "schema": schema })))
+        .collect();
+    json!({ "content": content })
+}
+
+fn upgrade_response(response: &Value, produces: &[String]) -> Value {
+    if response.get("$ref").is_some() {
+        return response.clone();
+    }
+    let mut upgraded = Map::new();
+    upgraded.insert(
+        "description".to_string(),
+        response.get("description").cloned().unwrap_or_else(|| json!("")),
+    );
+    let examples = object(response.get("examples"));
+    if let Some(schema) = response.get("schema") {
+        let mut schema = schema.clone();
+        if schema.get("type").and_then(Value::as_str) == Some("file") {
+            schema = json!({ "type": "string", "format": "binary" });
+        }
+        let content: Map<String, Value> = produces
+            .iter()
+            .map(|media_type| {
+                let mut media = json!({ "schema": schema });
+                if let Some(example) = examples.get(media_type) {
+                    media["example"] = example.clone();
+                }
+                (media_type.clone(), media)
+            })
+            .collect();
+        upgraded.insert("content".to_string(), Value::Object(content));
+    }
+    if let Some(headers) = response.get("headers") {
+        let headers: Map<String, Value> = object(Some(headers))
+            .iter()
+            .map(|(name, header)| {
+                let mut upgraded = json!({ "schema": parameter_schema(header) });
+                if let Some(description) = header.get("description") {
+                    upgraded["description"] = description.clone();
+                }
+                (name.clone(), upgraded)
+            })
+            .collect();
+        upgraded.insert("headers".to_string(), Value::Object(headers));
+    }
+    Value::Object(upgraded)
+}
+
+fn upgrade_security_scheme(scheme: &Value) -> Value {
+    let description = scheme.get("description").cloned();
+    let mut upgraded = match scheme.get("type").and_then(Value::as_str) {
+        Some("basic") => json!({ "type": "http", "scheme": "basic" }),
+        Some("oauth2") => {
+            let scopes = scheme.get("scopes").cloned().unwrap_or_else(|| json!({}));
+            let authorization_url = scheme.get("authorizationUrl").cloned().unwrap_or(Value::Null);
+            let token_url = scheme.get("tokenUrl").cloned().unwrap_or(Value::Null);
+            let (flow, flow_object) = match scheme.get("flow").and_then(Value::as_str) {
+                Some("implicit") => ("implicit", json!({ "authorizationUrl": authorization_url, "scopes": scopes })),
+                Some("password") => ("password", json!({ "tokenUrl": token_url, "scopes": scopes })),
+                Some("application") => ("clientCredentials", json!({ "tokenUrl": token_url, "scopes": scopes })),
+                _ => (
+                    "authorizationCode",
+                    json!({ "authorizationUrl": authorization_url, "tokenUrl": token_url, "scopes": scopes }),
+                ),
+            };
+            json!({ "type": "oauth2", "flows": { flow: flow_object } })
+        }
+        _ => scheme.clone(),
+    };
+    if let Some(description) = description {
+        upgraded["description"] = description;
+    }
+    upgraded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openapi::Spec;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    const PETSTORE: &str = r###"
+    {
+        "swagger": "2.0",
+        "info": { "title": "Petstore", "version": "1.0.0" },
+        "host": "petstore.swagger.io",
+        "basePath": "/v2",
+        "schemes": ["https", "http"],
+        "consumes": ["application/json"],
+        "produces": ["application/json", "application/xml"],
+        "paths": {
+            "/pet/{petId}": {
+                "parameters": [{ "name": "petId", "in": "path", "required": true, "type": "integer", "format": "int64" }],
+                "get": {
+                    "operationId": "getPetById",
+                    "produces": ["application/json"],
+                    "responses": {
+                        "200": {
+                            "description": "found",
+                            "schema": { "$ref": "#/definitions/Pet" },
+                            "headers": { "X-Rate-Limit": { "type": "integer", "description": "calls left" } },
+                            "examples": { "application/json": { "name": "doggie" } }
+                        },
+                        "404": { "$ref": "#/responses/NotFound" }
+                    },
+                    "security": [{ "api_key": [] }]
+                },
+                "post": {
+                    "operationId": "updatePetWithForm",
+                    "consumes": ["application/x-www-form-urlencoded"],
+                    "parameters": [
+                        { "name": "name", "in": "formData", "required": true, "type": "string" },
+                        { "name": "status", "in": "formData", "type": "string" }
+                    ],
+                    "responses": { "405": { "description": "invalid input" } }
+                }
+            },
+            "/pet": {
+                "put": {
+                    "parameters": [{ "$ref": "#/parameters/PetBody" }],
+                    "responses": {}
+                },
+                "post": {
+                    "parameters": [
+                        { "in": "body", "name": "body", "required": true, "schema": { "$ref": "#/definitions/Pet" } }
+                    ],
+                    "responses": {}
+                }
+            },
+            "/pet/findByStatus": {
+                "get": {
+                    "parameters": [
+                        { "name": "status", "in": "query", "type": "array", "items": { "type": "string", "enum": ["available", "sold"] }, "collectionFormat": "multi" },
+                        { "$ref": "#/parameters/Limit" }
+                    ],
+                    "responses": {}
+                }
+            },
+            "/pet/{petId}/uploadImage": {
+                "post": {
+                    "parameters": [{ "name": "file", "in": "formData", "type": "file" }, { "$ref": "#/parameters/Metadata" }],
+                    "responses": {}
+                }
+            }
+        },
+        "parameters": {
+            "PetBody": { "in": "body", "name": "body", "schema": { "$ref": "#/definitions/Pet" } },
+            "Limit": { "in": "query", "name": "limit", "type": "integer", "maximum": 100 },
+            "Metadata": { "in": "formData", "name": "additionalMetadata", "required": true, "type": "string" }
+        },
+        "responses": { "NotFound": { "description": "not found" } },
+        "securityDefinitions": {
+            "api_key": { "type": "apiKey", "name": "api_key", "in": "header" },
+            "basic": { "type": "basic" },
+            "petstore_auth": {
+                "type": "oauth2",
+                "flow": "accessCode",
+                "authorizationUrl": "https://petstore.swagger.io/oauth/authorize",
+                "tokenUrl": "https://petstore.swagger.io/oauth/token",
+                "scopes": { "read:pets": "read your pets" }
+            }
+        },
+        "definitions": {
+            "Pet": {
+                "type": "object",
+                "properties": { "name": { "type": "string" }, "category": { "$ref": "#/definitions/Category" } }
+            },
+            "Category": { "type": "object" }
+        }
+    }
+    "###;
+
+    fn upgraded() -> Value {
+        let document: Value = serde_json::from_str(PETSTORE).unwrap();
+        assert!(is_swagger2(&document));
+        upgrade("https://petstore.swagger.io/v2/swagger.json", &document)
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_servers() {
+        assert_eq!(
+            json!([{ "url": "https://petstore.swagger.io/v2" }, { "url": "http://petstore.swagger.io/v2" }]),
+            upgraded()["servers"]
+        );
+
+        let document = json!({ "swagger": "2.0", "basePath": "/api", "paths": {} });
+        assert_eq!(
+            json!([{ "url": "http://localhost:8080/api" }]),
+            upgrade("http://localhost:8080/swagger.json", &document)["servers"]
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_parameters_and_bodies() {
+        let paths = &upgraded()["paths"];
+
+        assert_eq!(
+            json!([{ "name": "petId", "in": "path", "required": true, "schema": { "type": "integer", "format": "int64" } }]),
+            paths["/pet/{petId}"]["parameters"]
+        );
+        assert_eq!(
+            json!({
+                "content": {
+                    "application/x-www-form-urlencoded": {
+                        "schema": {
+                            "type": "object",
+                            "properties": { "name": { "type": "string" }, "status": { "type": "string" } },
+                            "required": ["name"]
+                        }
+                    }
+                }
+            }),
+            paths["/pet/{petId}"]["post"]["requestBody"]
+        );
+        assert_eq!(json!([]), paths["/pet/{petId}"]["post"]["parameters"]);
+        assert_eq!(
+            json!({ "$ref": "#/components/requestBodies/PetBody" }),
+            paths["/pet"]["put"]["requestBody"]
+        );
+        assert_eq!(
+            json!({
+                "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } },
+                "required": true
+            }),
+            paths["/pet"]["post"]["requestBody"]
+        );
+        assert_eq!(
+            json!([
+                {
+                    "name": "status",
+                    "in": "query",
+                    "schema": { "type": "array", "items": { "type": "string", "enum": ["available", "sold"] } },
+                    "style": "form",
+                    "explode": true
+                },
+                { "$ref": "#/components/parameters/Limit" }
+            ]),
+            paths["/pet/findByStatus"]["get"]["parameters"]
+        );
+        assert_eq!(
+            json!({
+                "multipart/form-data": {
+                    "schema": {
+                        "type": "object",
+                        "properties": {
+                            "file": { "type": "string", "format": "binary" },
+                            "additionalMetadata": { "type": "string" }
+                        },
+                        "required": ["additionalMetadata"]
+                    }
+                }
+            }),
+            paths["/pet/{petId}/uploadImage"]["post"]["requestBody"]["content"]
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_responses() {
+        let responses = &upgraded()["paths"]["/pet/{petId}"]["get"]["responses"];
+
+        assert_eq!(
+            json!({
+                "200": {
+                    "description": "found",
+                    "content": {
+                        "application/json": {
+                            "schema": { "$ref": "#/components/schemas/Pet" },
+                            "example": { "name": "doggie" }
+                        }
+                    },
+                    "headers": { "X-Rate-Limit": { "schema": { "type": "integer" }, "description": "calls left" } }
+                },
+                "404": { "$ref": "#/components/responses/NotFound" }
+            }),
+            *responses
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_components() {
+        let components = &upgraded()["components"];
+
+        assert_eq!(
+            json!({ "$ref": "#/components/schemas/Category" }),
+            components["schemas"]["Pet"]["properties"]["category"]
+        );
+        assert_eq!(json!({ "type": "http", "scheme": "basic" }), components["securitySchemes"]["basic"]);
+        assert_eq!(
+            json!({
+                "type": "oauth2",
+                "flows": {
+                    "authorizationCode": {
+                        "authorizationUrl": "https://petstore.swagger.io/oauth/authorize",
+                        "tokenUrl": "https://petstore.swagger.io/oauth/token",
+                        "scopes": { "read:pets": "read your pets" }
+                    }
+                }
+            }),
+            components["securitySchemes"]["petstore_auth"]
+        );
+        assert_eq!(
+            json!({
+                "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } } },
+                "required": false
+            }),
+            components["requestBodies"]["PetBody"]
+        );
+        assert_eq!(
+            json!({ "name": "limit", "in": "query", "schema": { "type": "integer", "maximum": 100 } }),
+            components["parameters"]["Limit"]
+        );
+        assert!(components["parameters"].get("Metadata").is_none());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_shared_form_parameter() {
+        let document: Value = serde_json::from_str(PETSTORE).unwrap();
+        let spec = Spec::new("https://petstore.swagger.io/v2/swagger.json", document).unwrap();
+
+        let operation = spec.find_operation("POST", "/pet/{petId}/uploadImage").unwrap();
+        assert!(operation.parameters(&spec).is_empty());
+        assert!(operation.request_body(&spec).is_some());
+    }
+}
@@ -0,0 +1,426 @@
+use regex::Regex;
+use serde_json::{Map, Value};
+use super::{AdditionalProperties, Schema, Spec};
+
+/// A place where a value does not match its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// JSON pointer to the offending value, empty for the value itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Check `value` against `schema`, following refs through `spec`.
+pub fn validate(spec: &Spec, schema: &Schema, value: &Value) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    Validator { spec }.check(schema, value, "", &mut violations);
+    violations
+}
+
+/// Read `text` as the value `schema` describes, keeping it a string when it does
+/// not parse so that validation reports it.
+pub fn coerce(spec: &Spec, schema: &Schema, text: &str) -> Value {
+    let schema = spec.resolve_schema(schema);
+    let text = text.trim();
+    let parsed = match schema.primary_type() {
+        Some("integer") => text.parse::<i64>().ok().map(Value::from),
+        Some("number") => text.parse::<f64>().ok().map(Value::from),
+        Some("boolean") => text.parse::<bool>().ok().map(Value::from),
+        Some("object") | Some("array") => serde_json::from_str(text).ok(),
+        _ => None,
+    };
+    parsed
+        .or_else(|| (text == "null" && schema.is_nullable()).then_some(Value::Null))
+        .unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+struct Validator<'a> {
+    spec: &'a Spec,
+}
+
+impl Validator<'_> {
+    fn check(&self, schema: &Schema, value: &Value, path: &str, violations: &mut Vec<Violation>) {
+        let schema = self.spec.resolve_schema(schema);
+        let mut violation = |message: String| {
+            violations.push(Violation {
+                path: path.to_string(),
+                message,
+            })
+        };
+
+        if value.is_null() && schema.is_nullable() {
+            return;
+        }
+        let types = schema.types();
+        if !types.is_empty() && !types.iter().any(|schema_type| has_type(value, schema_type)) {
+            violation(format!("must be {}", types.join(" or ")));
+            return;
+        }
+        if let Some(expected) = &schema.const_value {
+            if !same(expected, value) {
+                violation(format!("must be {}", expected));
+            }
+        }
+        if let Some(values) = &schema.enumeration {
+            if !values.iter().any(|allowed| same(allowed, value)) {
+                let allowed: Vec<String> = values.iter().map(Value::to_string).collect();
+                violation(format!("must be one of {}", allowed.join(", ")));
+            }
+        }
+
+        match value {
+            Value::String(text) => self.check_string(&schema, text, &mut violation),
+            Value::Number(_) => self.check_number(&schema, value.as_f64().unwrap_or_default(), &mut violation),
+            _ => {}
+        }
+        if let Value::Array(items) = value {
+            self.check_array(&schema, items, path, violations);
+        }
+        if let Value::Object(members) = value {
+            self.check_object(&schema, members, path, violations);
+        }
+        self.check_composition(&schema, value, path, violations);
+    }
+
+    fn check_string(&self, schema: &Schema, text: &str, violation: &mut impl FnMut(String)) {
+        let length = text.chars().count() as u64;
+        if let Some(min_length) = schema.min_length.filter(|min_length| length < *min_length) {
+            violation(format!("must be at least {} characters long", min_length));
+        }
+        if let Some(max_length) = schema.max_length.filter(|max_length| length > *max_length) {
+            violation(format!("must be at most {} characters long", max_length));
+        }
+        if let Some(pattern) = &schema.pattern {
+            // patterns this engine cannot compile are not enforced
+            if let Ok(regex) = Regex::new(pattern) {
+                if !regex.is_match(text) {
+                    violation(format!("must match {}", pattern));
+                }
+            }
+        }
+        if let Some(format) = &schema.format {
+            if !matches_format(format, text) {
+                violation(format!("must be a valid {}", format));
+            }
+        }
+    }
+
+    fn check_number(&self, schema: &Schema, number: f64, violation: &mut impl FnMut(String)) {
+        match schema.lower_bound() {
+            Some((minimum, true)) if number <= minimum => violation(format!("must be greater than {}", minimum)),
+            Some((minimum, false)) if number < minimum => violation(format!("must be at least {}", minimum)),
+            _ => {}
+        }
+        match schema.upper_bound() {
+            Some((maximum, true)) if number >= maximum => violation(format!("must be less than {}", maximum)),
+            Some((maximum, false)) if number > maximum => violation(format!("must be at most {}", maximum)),
+            _ => {}
+        }
+        if let Some(multiple_of) = schema.multiple_of.filter(|multiple_of| *multiple_of > 0.0) {
+            let quotient = number / multiple_of;
+            if (quotient - quotient.round()).abs() > 1e-9 {
+                violation(format!("must be a multiple of {}", multiple_of));
+            }
+        }
+        let range = match schema.format.as_deref() {
+            Some("int32") => Some((i32::MIN as f64, i32::MAX as f64)),
+            Some("int64") => Some((i64::MIN as f64, i64::MAX as f64)),
+            _ => None,
+        };
+        if let Some((low, high)) = range.filter(|(low, high)| number < *low || number > *high) {
+            violation(format!("must be between {} and {}", low, high));
+        }
+    }
+
+    fn check_array(&self, schema: &Schema, items: &[Value], path: &str, violations: &mut Vec<Violation>) {
+        let count = items.len() as u64;
+        let mut violation = |message: String| {
+            violations.push(Violation {
+                path: path.to_string(),
+                message,
+            })
+        };
+        if let Some(min_items) = schema.min_items.filter(|min_items| count < *min_items) {
+            violation(format!("must have at least {} items", min_items));
+        }
+        if let Some(max_items) = schema.max_items.filter(|max_items| count > *max_items) {
+            violation(format!("must have at most {} items", max_items));
+        }
+        if schema.unique_items {
+            let duplicate = items
+                .iter()
+                .enumerate()
+                .any(|(i, item)| items[..i].iter().any(|other| same(other, item)));
+            if duplicate {
+                violation("must not contain duplicates".to_string());
+            }
+        }
+
+        for (index, item) in items.iter().enumerate() {
+            let item_path = format!("{}/{}", path, index);
+            match (schema.prefix_items.get(index), &schema.items) {
+                (Some(prefix), _) => self.check(prefix, item, &item_path, violations),
+                (None, Some(items)) if items.not.is_some() && index >= schema.prefix_items.len() => {
+                    violations.push(Violation {
+                        path: item_path,
+                        message: "is not allowed".to_string(),
+                    });
+                }
+                (None, Some(items)) => self.check(items, item, &item_path, violations),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn check_object(&self, schema: &Schema, members: &Map<String, Value>, path: &str, violations: &mut Vec<Violation>) {
+        for name in &schema.required {
+            if !members.contains_key(name) {
+                violations.push(Violation {
+                    path: format!("{}/{}", path, name),
+                    message: "is required".to_string(),
+                });
+            }
+        }
+        let count = members.len() as u64;
+        if let Some(min_properties) = schema.min_properties.filter(|min_properties| count < *min_properties) {
+            violations.push(Violation {
+                path: path.to_string(),
+                message: format!("must have at least {} properties", min_properties),
+            });
+        }
+        if let Some(max_properties) = schema.max_properties.filter(|max_properties| count > *max_properties) {
+            violations.push(Violation {
+                path: path.to_string(),
+                message: format!("must have at most {} properties", max_properties),
+            });
+        }
+
+        for (name, member) in members {
+            let member_path = format!("{}/{}", path, name);
+            match (schema.properties.get(name), &schema.additional_properties) {
+                (Some(property), _) => self.check(property, member, &member_path, violations),
+                (None, Some(AdditionalProperties::Allowed(false))) => violations.push(Violation {
+                    path: member_path,
+                    message: "is not a known property".to_string(),
+                }),
+                (None, Some(AdditionalProperties::Schema(additional))) => {
+                    self.check(additional, member, &member_path, violations)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_composition(&self, schema: &Schema, value: &Value, path: &str, violations: &mut Vec<Violation>) {
+        for member in &schema.all_of {
+            self.check(member, value, path, violations);
+        }
+        let matching = |members: &[Schema]| {
+            members
+                .iter()
+                .filter(|member| validate_quietly(self, member, value))
+                .count()
+        };
+        if !schema.any_of.is_empty() && matching(&schema.any_of) == 0 {
+            violations.push(Violation {
+                path: path.to_string(),
+                message: "must match at least one of the anyOf schemas".to_string(),
+            });
+        }
+        if !schema.one_of.is_empty() && matching(&schema.one_of) != 1 {
+            violations.push(Violation {
+                path: path.to_string(),
+                message: "must match exactly one of the oneOf schemas".to_string(),
+            });
+        }
+        if let Some(not) = &schema.not {
+            if validate_quietly(self, not, value) {
+                violations.push(Violation {
+                    path: path.to_string(),
+                    message: "must not match the not schema".to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn validate_quietly(validator: &Validator, schema: &Schema, value: &Value) -> bool {
+    let mut violations = Vec::new();
+    validator.check(schema, value, "", &mut violations);
+    violations.is_empty()
+}
+
+fn has_type(value: &Value, schema_type: &str) -> bool {
+    match schema_type {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.as_f64().is_some_and(|number| number.fract() == 0.0),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// JSON equality where `1` and `1.0` are the same number.
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn matches_format(format: &str, text: &str) -> bool {
+    let pattern = match format {
+        "date-time" => r"^\d{4}-\d{2}-\d{2}[Tt ]\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$",
+        "date" => r"^\d{4}-\d{2}-\d{2}$",
+        "time" => r"^\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})?$",
+        "email" => r"^[^@\s]+@[^@\s]+\.[^@\s]+$",
+        "uuid" => r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+        "uri" | "url" => r"^[A-Za-z][A-Za-z0-9+.-]*:\S*$",
+        "hostname" => r"^[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?(\.[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?)*$",
+        "byte" => r"^[A-Za-z0-9+/]*={0,2}$",
+        "ipv4" => return text.parse::<std::net::Ipv4Addr>().is_ok(),
+        "ipv6" => return text.parse::<std::net::Ipv6Addr>().is_ok(),
+        // unknown formats are annotations only
+        _ => return true,
+    };
+    Regex::new(pattern).map(|regex| regex.is_match(text)).unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn spec() -> Spec {
+        Spec::new(
+            "https://example.com/openapi.json",
+            json!({
+                "openapi": "3.1.0",
+                "components": {
+                    "schemas": {
+                        "Pet": {
+                            "type": "object",
+                            "required": ["name", "status"],
+                            "additionalProperties": false,
+                            "properties": {
+                                "name": { "type": "string", "minLength": 2, "maxLength": 5, "pattern": "^[A-Z]" },
+                                "status": { "enum": ["available", "sold"] },
+                                "age": { "type": "integer", "exclusiveMinimum": 0, "maximum": 30 },
+                                "weight": { "type": ["number", "null"], "multipleOf": 0.5 },
+                                "born": { "type": "string", "format": "date" },
+                                "owner": { "type": "string", "format": "email" },
+                                "tags": { "type": "array", "items": { "type": "string" }, "maxItems": 2, "uniqueItems": true },
+                                "kind": { "oneOf": [{ "const": "dog" }, { "const": "cat" }] }
+                            }
+                        }
+                    }
+                }
+            }),
+        )
+        .unwrap()
+    }
+
+    fn pet() -> Schema {
+        Schema {
+            reference: Some("#/components/schemas/Pet".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn messages(violations: Vec<Violation>) -> Vec<String> {
+        violations.iter().map(Violation::to_string).collect()
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_valid_value() {
+        let spec = spec();
+        let value = json!({
+            "name": "Rex",
+            "status": "sold",
+            "age": 3,
+            "weight": null,
+            "born": "2020-02-29",
+            "owner": "me@example.com",
+            "tags": ["good"],
+            "kind": "dog"
+        });
+
+        assert_eq!(Vec::<String>::new(), messages(validate(&spec, &pet(), &value)));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_violations() {
+        let spec = spec();
+        let value = json!({
+            "name": "rexxxxx",
+            "age": 0,
+            "weight": 1.2,
+            "born": "yesterday",
+            "owner": "nobody",
+            "tags": ["a", "a", "b"],
+            "kind": "cow",
+            "color": "brown"
+        });
+
+        assert_eq!(
+            vec![
+                "/status: is required",
+                "/name: must be at most 5 characters long",
+                "/name: must match ^[A-Z]",
+                "/age: must be greater than 0",
+                "/weight: must be a multiple of 0.5",
+                "/born: must be a valid date",
+                "/owner: must be a valid email",
+                "/tags: must have at most 2 items",
+                "/tags: must not contain duplicates",
+                "/kind: must match exactly one of the oneOf schemas",
+                "/color: is not a known property",
+            ],
+            messages(validate(&spec, &pet(), &value))
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_types() {
+        let spec = spec();
+        let integer = Schema {
+            schema_type: Some(crate::openapi::SchemaType::Single("integer".to_string())),
+            format: Some("int32".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(vec!["must be integer"], messages(validate(&spec, &integer, &json!("1"))));
+        assert_eq!(vec!["must be integer"], messages(validate(&spec, &integer, &json!(1.5))));
+        assert_eq!(
+            vec!["must be between -2147483648 and 2147483647"],
+            messages(validate(&spec, &integer, &json!(3_000_000_000u64)))
+        );
+        assert!(validate(&spec, &integer, &json!(2.0)).is_empty());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_coerce() {
+        let spec = spec();
+        let age = &spec.api.components.schemas["Pet"].properties["age"];
+        let tags = &spec.api.components.schemas["Pet"].properties["tags"];
+
+        assert_eq!(json!(3), coerce(&spec, age, " 3 "));
+        assert_eq!(json!("three"), coerce(&spec, age, "three"));
+        assert_eq!(json!(["a"]), coerce(&spec, tags, r#"["a"]"#));
+    }
+}
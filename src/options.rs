@@ -23,6 +23,41 @@ pub struct AuthBtnOptions {
     pub class_name: Option<String>,
 }
 
+#[serde_inline_default]
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, serde_derive_default::Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuth2Options {
+    /** client id prefilled in the Authorize dialog */
+    pub client_id: Option<String>,
+    /** client secret prefilled in the Authorize dialog, only for confidential test clients */
+    pub client_secret: Option<String>,
+    /** page the authorization server redirects to, on the origin of the current page; defaults to oauth2-redirect.html next to it */
+    pub redirect_url: Option<String>,
+    /** send a PKCE code challenge with the authorization code flow */
+    #[serde_inline_default(true)]
+    pub use_pkce: bool,
+}
+
+#[serde_inline_default]
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, serde_derive_default::Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryOptions {
+    /** keep the requests sent from every operation so they can be restored */
+    #[serde_inline_default(true)]
+    pub enabled: bool,
+    /** how many requests are kept per operation */
+    #[serde_inline_default(10)]
+    pub limit: u32,
+    /** where the history is kept: "localStorage", "sessionStorage" or "memory" to forget it on reload */
+    #[serde_inline_default("localStorage".to_string())]
+    pub storage: String,
+    /** replace the credentials of the Authorize dialog with a placeholder before keeping a request */
+    #[serde_inline_default(true)]
+    pub redact_secrets: bool,
+}
+
 #[wasm_bindgen(getter_with_clone)]
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, serde_derive_default::Default)]
 #[serde(rename_all = "camelCase")]
@@ -40,6 +75,28 @@ pub struct TryBtnOptions {
 pub struct RedocTryItOutOptions {
     #[serde_inline_default("2.1.3".to_string())]
     pub redoc_version: String,
+    /** url of the Redoc standalone bundle, e.g. a copy served with the documentation; overrides assetBaseUrl */
+    pub redoc_bundle_url: Option<String>,
+    /** npm CDN or mirror the Redoc bundle is loaded from, defaults to https://cdn.jsdelivr.net/npm */
+    pub asset_base_url: Option<String>,
+    /** Subresource Integrity of the Redoc bundle, e.g. "sha384-…"; defaults to the known hash of redocVersion */
+    pub redoc_integrity: Option<String>,
+    /** crossorigin attribute of the injected script tags, "anonymous" when an integrity is checked */
+    pub cross_origin: Option<String>,
+    /** CSP nonce set on the injected script and style tags */
+    pub nonce: Option<String>,
+    /** refuse to load a Redoc bundle whose integrity is not known */
+    #[serde(default)]
+    pub strict_integrity: bool,
+    /** bundles tried in order when the Redoc bundle can not be loaded, with the same integrity */
+    #[serde(default)]
+    pub redoc_fallback_urls: Vec<String>,
+    /** milliseconds to wait for a script before trying the next url, 0 waits forever */
+    #[serde_inline_default(30000)]
+    pub script_timeout_ms: u32,
+    /** how many more times a script url is tried after it failed */
+    #[serde_inline_default(1)]
+    pub script_retries: u32,
     #[serde_inline_default(true)]
     pub try_it_out_enabled: bool,
     #[serde_inline_default("try-out-wrapper".to_string())]
@@ -50,12 +107,21 @@ pub struct RedocTryItOutOptions {
     pub operation_box_selector: String,
     #[serde_inline_default("try".to_string())]
     pub selected_operation_class: String,
+    /** also load jQuery and jquery.scrollTo at dependenciesVersions, for custom scripts written against them */
+    #[serde(default)]
+    pub legacy_jquery: bool,
     #[serde(default)]
     pub dependencies_versions: DependenciesVersions,
     #[serde(default)]
     pub auth_btn: AuthBtnOptions,
     #[serde(default)]
     pub try_btn: TryBtnOptions,
+    #[serde(default)]
+    pub oauth2: OAuth2Options,
+    /** base url requests are sent to instead of the servers of the spec, e.g. a local development server */
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub history: HistoryOptions,
 }
 
 #[wasm_bindgen(getter_with_clone)]
@@ -177,11 +243,21 @@ mod tests {
 
         let expected = RedocTryItOutOptions {
             redoc_version: "9.9.9".to_string(),
+            redoc_bundle_url: None,
+            asset_base_url: None,
+            redoc_integrity: None,
+            cross_origin: None,
+            nonce: None,
+            strict_integrity: false,
+            redoc_fallback_urls: vec![],
+            script_timeout_ms: 30000,
+            script_retries: 1,
             try_it_out_enabled: true,
             try_it_box_container_id: "try-out-wrapper".to_string(),
             container_id: "custom-redoc-container".to_string(),
             operation_box_selector: "[data-section-id]".to_string(),
             selected_operation_class: "try".to_string(),
+            legacy_jquery: false,
             dependencies_versions: DependenciesVersions {
                 jquery: "3.5.1".to_string(),
                 jquery_scroll_to: "4.4.4".to_string(),
@@ -197,6 +273,19 @@ mod tests {
                 class_name: Some("try-btn".to_string()),
                 selected_class_name: Some("try-btn-selected".to_string()),
             },
+            oauth2: OAuth2Options {
+                client_id: None,
+                client_secret: None,
+                redirect_url: None,
+                use_pkce: true,
+            },
+            base_url: None,
+            history: HistoryOptions {
+                enabled: true,
+                limit: 10,
+                storage: "localStorage".to_string(),
+                redact_secrets: true,
+            },
         };
 
         let actual: RedocTryItOutOptions =
@@ -286,11 +375,21 @@ mod tests {
     fn test_redoc_try_it_out_options() {
         let redoc_try_it_out_options: RedocTryItOutOptions = RedocTryItOutOptions {
             redoc_version: "2.1.3".to_string(),
+            redoc_bundle_url: None,
+            asset_base_url: Some("https://npm.example.com/".to_string()),
+            redoc_integrity: Some("sha384-abc".to_string()),
+            cross_origin: Some("use-credentials".to_string()),
+            nonce: Some("r4nd0m".to_string()),
+            strict_integrity: true,
+            redoc_fallback_urls: vec!["https://unpkg.com/redoc@2.1.3/bundles/redoc.standalone.js".to_string()],
+            script_timeout_ms: 5000,
+            script_retries: 0,
             try_it_out_enabled: true,
             try_it_box_container_id: "try-out-wrapper".to_string(),
             container_id: "redoc-container".to_string(),
             operation_box_selector: "[data-section-id]".to_string(),
             selected_operation_class: "try".to_string(),
+            legacy_jquery: true,
             dependencies_versions: DependenciesVersions {
                 jquery: "3.5.1".to_string(),
                 jquery_scroll_to: "2.1.3".to_string(),
@@ -306,6 +405,19 @@ mod tests {
                 class_name: Some("class".to_string()),
                 selected_class_name: Some("selected_class".to_string()),
             },
+            oauth2: OAuth2Options {
+                client_id: Some("client".to_string()),
+                client_secret: None,
+                redirect_url: Some("https://example.com/oauth2-redirect.html".to_string()),
+                use_pkce: false,
+            },
+            base_url: Some("http://localhost:8080".to_string()),
+            history: HistoryOptions {
+                enabled: true,
+                limit: 5,
+                storage: "sessionStorage".to_string(),
+                redact_secrets: false,
+            },
         };
 
         // Serialize the struct to a JsValue
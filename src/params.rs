@@ -0,0 +1,297 @@
+//! Serialization of parameter values following the `style` and `explode`
+//! rules of the OpenAPI specification (section "Style Values").
+
+use super::http::{encode_component, Pairs};
+use super::openapi::{Parameter, ParameterLocation};
+
+/// A parameter value, shaped by the type of its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    Primitive(String),
+    Array(Vec<String>),
+    Object(Pairs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Matrix,
+    Label,
+    Form,
+    Simple,
+    SpaceDelimited,
+    PipeDelimited,
+    DeepObject,
+}
+
+impl Style {
+    pub fn parse(style: &str) -> Option<Style> {
+        Some(match style {
+            "matrix" => Style::Matrix,
+            "label" => Style::Label,
+            "form" => Style::Form,
+            "simple" => Style::Simple,
+            "spaceDelimited" => Style::SpaceDelimited,
+            "pipeDelimited" => Style::PipeDelimited,
+            "deepObject" => Style::DeepObject,
+            _ => return None,
+        })
+    }
+
+    pub fn default_for(location: ParameterLocation) -> Style {
+        match location {
+            ParameterLocation::Query | ParameterLocation::Cookie => Style::Form,
+            ParameterLocation::Path | ParameterLocation::Header => Style::Simple,
+        }
+    }
+}
+
+/// How a parameter is written into the request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Serialization {
+    pub style: Style,
+    pub explode: bool,
+    pub allow_reserved: bool,
+}
+
+impl Serialization {
+    /// The declared serialization of `parameter`, with the defaults of its location.
+    pub fn of(parameter: &Parameter) -> Serialization {
+        let style = parameter
+            .style
+            .as_deref()
+            .and_then(Style::parse)
+            .unwrap_or_else(|| Style::default_for(parameter.location));
+        Serialization {
+            style,
+            explode: parameter.explode.unwrap_or(style == Style::Form),
+            allow_reserved: parameter.allow_reserved,
+        }
+    }
+}
+
+/// The text replacing `{name}` in the path.
+pub fn path_value(name: &str, value: &ParamValue, serialization: Serialization) -> String {
+    let explode = serialization.explode;
+    let encode = |text: &str| encode_component(text);
+    match serialization.style {
+        Style::Label => {
+            let separator = if explode { "." } else { "," };
+            format!(".{}", join(value, separator, explode, encode))
+        }
+        Style::Matrix => match value {
+            ParamValue::Primitive(text) => format!(";{}={}", encode(name), encode(text)),
+            ParamValue::Array(items) if explode => items
+                .iter()
+                .map(|item| format!(";{}={}", encode(name), encode(item)))
+                .collect(),
+            ParamValue::Object(members) if explode => members
+                .iter()
+                .map(|(key, item)| format!(";{}={}", encode(key), encode(item)))
+                .collect(),
+            _ => format!(";{}={}", encode(name), join(value, ",", false, encode)),
+        },
+        _ => join(value, ",", explode, encode),
+    }
+}
+
+/// Encoded `name=value` pieces of the query string.
+pub fn query_pieces(name: &str, value: &ParamValue, serialization: Serialization) -> Vec<String> {
+    let encode = |text: &str| {
+        if serialization.allow_reserved {
+            encode_allowing_reserved(text)
+        } else {
+            encode_component(text)
+        }
+    };
+    let name = encode_component(name);
+    match (serialization.style, value, serialization.explode) {
+        (_, ParamValue::Primitive(text), _) => vec![format!("{}={}", name, encode(text))],
+        (Style::DeepObject, ParamValue::Object(members), _) => members
+            .iter()
+            .map(|(key, item)| format!("{}[{}]={}", name, encode_component(key), encode(item)))
+            .collect(),
+        (_, ParamValue::Array(items), true) => items.iter().map(|item| format!("{}={}", name, encode(item))).collect(),
+        (_, ParamValue::Object(members), true) => members
+            .iter()
+            .map(|(key, item)| format!("{}={}", encode_component(key), encode(item)))
+            .collect(),
+        (Style::SpaceDelimited, _, false) => vec![format!("{}={}", name, join(value, "%20", false, encode))],
+        (Style::PipeDelimited, _, false) => vec![format!("{}={}", name, join(value, "|", false, encode))],
+        _ => vec![format!("{}={}", name, join(value, ",", false, encode))],
+    }
+}
+
+/// A header value; headers are always `simple`.
+pub fn header_value(value: &ParamValue, serialization: Serialization) -> String {
+    join(value, ",", serialization.explode, |text| text.to_string())
+}
+
+/// Cookie name/value pairs; cookies are always `form`.
+pub fn cookie_pairs(name: &str, value: &ParamValue, serialization: Serialization) -> Pairs {
+    match (value, serialization.explode) {
+        (ParamValue::Array(items), true) => items.iter().map(|item| (name.to_string(), item.clone())).collect(),
+        (ParamValue::Object(members), true) => members.clone(),
+        _ => vec![(name.to_string(), join(value, ",", false, |text| text.to_string()))],
+    }
+}
+
+/// Replace the `{name}` templates of `path` with already serialized values.
+pub fn expand_path(path: &str, values: &[(String, String)]) -> String {
+    values.iter().fold(path.to_string(), |path, (name, value)| {
+        path.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// Values joined by `separator`; object members are `key=value` when exploded and
+/// separated like the values otherwise.
+fn join(value: &ParamValue, separator: &str, explode: bool, encode: impl Fn(&str) -> String) -> String {
+    match value {
+        ParamValue::Primitive(text) => encode(text),
+        ParamValue::Array(items) => items.iter().map(|item| encode(item)).collect::<Vec<_>>().join(separator),
+        ParamValue::Object(members) => members
+            .iter()
+            .map(|(key, item)| {
+                if explode {
+                    format!("{}={}", encode(key), encode(item))
+                } else {
+                    format!("{}{}{}", encode(key), separator, encode(item))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(separator),
+    }
+}
+
+/// Percent-encode everything but the unreserved and reserved characters of RFC 3986,
+/// for query parameters with `allowReserved`.
+fn encode_allowing_reserved(value: &str) -> String {
+    const RESERVED: &str = ":/?#[]@!$&'()*+,;=";
+    value
+        .chars()
+        .map(|c| {
+            if RESERVED.contains(c) {
+                c.to_string()
+            } else {
+                encode_component(&c.to_string())
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    fn serialization(style: Style, explode: bool) -> Serialization {
+        Serialization {
+            style,
+            explode,
+            allow_reserved: false,
+        }
+    }
+
+    fn primitive() -> ParamValue {
+        ParamValue::Primitive("5".to_string())
+    }
+
+    fn array() -> ParamValue {
+        ParamValue::Array(vec!["3".to_string(), "4".to_string(), "5".to_string()])
+    }
+
+    fn object() -> ParamValue {
+        ParamValue::Object(vec![
+            ("role".to_string(), "admin".to_string()),
+            ("firstName".to_string(), "Alex".to_string()),
+        ])
+    }
+
+    // the expected values are those of the style examples table of the specification
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_path_styles() {
+        let cases = [
+            (Style::Simple, false, "5", "3,4,5", "role,admin,firstName,Alex"),
+            (Style::Simple, true, "5", "3,4,5", "role=admin,firstName=Alex"),
+            (Style::Label, false, ".5", ".3,4,5", ".role,admin,firstName,Alex"),
+            (Style::Label, true, ".5", ".3.4.5", ".role=admin.firstName=Alex"),
+            (Style::Matrix, false, ";id=5", ";id=3,4,5", ";id=role,admin,firstName,Alex"),
+            (Style::Matrix, true, ";id=5", ";id=3;id=4;id=5", ";role=admin;firstName=Alex"),
+        ];
+        for (style, explode, expected_primitive, expected_array, expected_object) in cases {
+            let serialization = serialization(style, explode);
+            assert_eq!(expected_primitive, path_value("id", &primitive(), serialization));
+            assert_eq!(expected_array, path_value("id", &array(), serialization));
+            assert_eq!(expected_object, path_value("id", &object(), serialization));
+        }
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_query_styles() {
+        let cases = [
+            (Style::Form, false, "id=5", "id=3,4,5", "id=role,admin,firstName,Alex"),
+            (Style::Form, true, "id=5", "id=3&id=4&id=5", "role=admin&firstName=Alex"),
+            (Style::SpaceDelimited, false, "id=5", "id=3%204%205", "id=role%20admin%20firstName%20Alex"),
+            (Style::PipeDelimited, false, "id=5", "id=3|4|5", "id=role|admin|firstName|Alex"),
+            (Style::DeepObject, true, "id=5", "id=3&id=4&id=5", "id[role]=admin&id[firstName]=Alex"),
+        ];
+        for (style, explode, expected_primitive, expected_array, expected_object) in cases {
+            let serialization = serialization(style, explode);
+            assert_eq!(expected_primitive, query_pieces("id", &primitive(), serialization).join("&"));
+            assert_eq!(expected_array, query_pieces("id", &array(), serialization).join("&"));
+            assert_eq!(expected_object, query_pieces("id", &object(), serialization).join("&"));
+        }
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_values_are_encoded() {
+        let value = ParamValue::Array(vec!["a b".to_string(), "c,d/e".to_string()]);
+
+        assert_eq!(
+            vec!["q=a%20b,c%2Cd%2Fe".to_string()],
+            query_pieces("q", &value, serialization(Style::Form, false))
+        );
+        assert_eq!("a%20b,c%2Cd%2Fe", path_value("q", &value, serialization(Style::Simple, false)));
+
+        let reserved = Serialization {
+            allow_reserved: true,
+            ..serialization(Style::Form, true)
+        };
+        assert_eq!(
+            vec!["next=/pets?page=2&size=1%20".to_string()],
+            query_pieces("next", &ParamValue::Primitive("/pets?page=2&size=1 ".to_string()), reserved)
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_headers_and_cookies() {
+        assert_eq!("3,4,5", header_value(&array(), serialization(Style::Simple, false)));
+        assert_eq!("role=admin,firstName=Alex", header_value(&object(), serialization(Style::Simple, true)));
+
+        assert_eq!(
+            vec![("id".to_string(), "3,4,5".to_string())],
+            cookie_pairs("id", &array(), serialization(Style::Form, false))
+        );
+        assert_eq!(
+            vec![("role".to_string(), "admin".to_string()), ("firstName".to_string(), "Alex".to_string())],
+            cookie_pairs("id", &object(), serialization(Style::Form, true))
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_default_serialization() {
+        let parameter: Parameter = serde_json::from_str(r#"{ "name": "id", "in": "query" }"#).unwrap();
+        assert_eq!(serialization(Style::Form, true), Serialization::of(&parameter));
+
+        let parameter: Parameter = serde_json::from_str(r#"{ "name": "id", "in": "path", "style": "label" }"#).unwrap();
+        assert_eq!(serialization(Style::Label, false), Serialization::of(&parameter));
+
+        assert_eq!(
+            "/pet/5/photos/.3.4.5",
+            expand_path("/pet/{petId}/photos/{ids}", &[
+                ("petId".to_string(), "5".to_string()),
+                ("ids".to_string(), ".3.4.5".to_string()),
+            ])
+        );
+    }
+}
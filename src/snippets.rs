@@ -0,0 +1,520 @@
+//! Code that sends a composed request from outside the browser.
+
+use super::http::{Body, HttpRequest, Pairs, Part, PartValue};
+
+/// What masked secrets are replaced with.
+pub const SECRET_PLACEHOLDER: &str = "<secret>";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Curl,
+    Httpie,
+    Fetch,
+    Python,
+    Reqwest,
+}
+
+impl Language {
+    pub const ALL: [Language; 5] = [
+        Language::Curl,
+        Language::Httpie,
+        Language::Fetch,
+        Language::Python,
+        Language::Reqwest,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::Curl => "curl",
+            Language::Httpie => "HTTPie",
+            Language::Fetch => "JavaScript fetch",
+            Language::Python => "Python requests",
+            Language::Reqwest => "Rust reqwest",
+        }
+    }
+}
+
+/// `request` written in `language`.
+pub fn snippet(language: Language, request: &HttpRequest) -> String {
+    match language {
+        Language::Curl => curl(request),
+        Language::Httpie => httpie(request),
+        Language::Fetch => fetch(request),
+        Language::Python => python(request),
+        Language::Reqwest => reqwest(request),
+    }
+}
+
+/// A copy of `request` with every occurrence of `secrets` replaced by `SECRET_PLACEHOLDER`.
+pub fn mask_secrets(request: &HttpRequest, secrets: &[String]) -> HttpRequest {
+    let mask = |text: &str| {
+        secrets
+            .iter()
+            .filter(|secret| !secret.is_empty())
+            .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), SECRET_PLACEHOLDER))
+    };
+    let mask_pairs = |pairs: &Pairs| pairs.iter().map(|(name, value)| (name.clone(), mask(value))).collect();
+    HttpRequest {
+        url: mask(&request.url),
+        query: mask_pairs(&request.query),
+        headers: mask_pairs(&request.headers),
+        cookies: mask_pairs(&request.cookies),
+        ..request.clone()
+    }
+}
+
+/// Headers as sent; the tools pick the multipart boundary and its Content-Type themselves.
+fn sent_headers(request: &HttpRequest) -> Pairs {
+    let multipart = matches!(request.body, Some(Body::Multipart(_)));
+    request
+        .headers
+        .iter()
+        .filter(|(name, _)| !(multipart && name.eq_ignore_ascii_case("content-type")))
+        .cloned()
+        .collect()
+}
+
+/// `sent_headers` with the cookies folded into a `Cookie` header.
+fn headers_with_cookie(request: &HttpRequest) -> Pairs {
+    let mut headers = sent_headers(request);
+    if let Some(cookie) = cookie_header(request) {
+        headers.push(("Cookie".to_string(), cookie));
+    }
+    headers
+}
+
+fn cookie_header(request: &HttpRequest) -> Option<String> {
+    if request.cookies.is_empty() {
+        return None;
+    }
+    let cookies: Vec<String> = request
+        .cookies
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    Some(cookies.join("; "))
+}
+
+fn file_name(part: &Part) -> String {
+    match &part.value {
+        PartValue::File(file) => file.name(),
+        PartValue::Text(_) => String::new(),
+    }
+}
+
+/// Single quoted for POSIX shells.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// A double quoted string literal, valid in JavaScript and Python.
+fn string_literal(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_default()
+}
+
+fn curl(request: &HttpRequest) -> String {
+    let mut lines = vec![if request.method == "GET" {
+        format!("curl {}", shell_quote(&request.full_url()))
+    } else {
+        format!("curl -X {} {}", request.method, shell_quote(&request.full_url()))
+    }];
+    for (name, value) in sent_headers(request) {
+        lines.push(format!("-H {}", shell_quote(&format!("{}: {}", name, value))));
+    }
+    if let Some(cookie) = cookie_header(request) {
+        lines.push(format!("-b {}", shell_quote(&cookie)));
+    }
+    match &request.body {
+        None => {}
+        Some(Body::Text(text)) => lines.push(format!("--data-raw {}", shell_quote(text))),
+        Some(Body::UrlEncoded(pairs)) => lines.extend(
+            pairs
+                .iter()
+                .map(|(name, value)| format!("--data-urlencode {}", shell_quote(&format!("{}={}", name, value)))),
+        ),
+        Some(Body::Multipart(parts)) => lines.extend(parts.iter().map(|part| {
+            let field = match (&part.value, &part.content_type) {
+                (PartValue::File(_), _) => format!("{}=@{}", part.name, file_name(part)),
+                (PartValue::Text(text), Some(content_type)) => format!("{}={};type={}", part.name, text, content_type),
+                (PartValue::Text(text), None) => format!("{}={}", part.name, text),
+            };
+            format!("-F {}", shell_quote(&field))
+        })),
+    }
+    lines.join(" \\\n  ")
+}
+
+fn httpie(request: &HttpRequest) -> String {
+    let mode = match &request.body {
+        Some(Body::UrlEncoded(_)) => "--form ",
+        Some(Body::Multipart(_)) => "--multipart ",
+        _ => "",
+    };
+    let mut lines = vec![format!("http {}{} {}", mode, request.method, shell_quote(&request.full_url()))];
+    lines.extend(
+        headers_with_cookie(request)
+            .iter()
+            .map(|(name, value)| shell_quote(&format!("{}:{}", name, value))),
+    );
+    match &request.body {
+        None => {}
+        Some(Body::Text(text)) => lines.push(format!("--raw {}", shell_quote(text))),
+        Some(Body::UrlEncoded(pairs)) => {
+            lines.extend(pairs.iter().map(|(name, value)| shell_quote(&format!("{}={}", name, value))))
+        }
+        Some(Body::Multipart(parts)) => lines.extend(parts.iter().map(|part| match &part.value {
+            PartValue::File(_) => shell_quote(&format!("{}@{}", part.name, file_name(part))),
+            PartValue::Text(text) => shell_quote(&format!("{}={}", part.name, text)),
+        })),
+    }
+    lines.join(" \\\n  ")
+}
+
+fn fetch(request: &HttpRequest) -> String {
+    let mut code = String::new();
+    let body = match &request.body {
+        None => None,
+        Some(Body::Text(text)) => Some(string_literal(text)),
+        Some(Body::UrlEncoded(pairs)) => {
+            let pairs: Vec<String> = pairs
+                .iter()
+                .map(|(name, value)| format!("[{}, {}]", string_literal(name), string_literal(value)))
+                .collect();
+            Some(format!("new URLSearchParams([{}])", pairs.join(", ")))
+        }
+        Some(Body::Multipart(parts)) => {
+            code.push_str("const body = new FormData();\n");
+            for part in parts {
+                let value = match (&part.value, &part.content_type) {
+                    (PartValue::File(_), _) => format!("file /* {} */", file_name(part)),
+                    (PartValue::Text(text), Some(content_type)) => format!(
+                        "new Blob([{}], {{ type: {} }})",
+                        string_literal(text),
+                        string_literal(content_type)
+                    ),
+                    (PartValue::Text(text), None) => string_literal(text),
+                };
+                code.push_str(&format!("body.append({}, {});\n", string_literal(&part.name), value));
+            }
+            Some("body".to_string())
+        }
+    };
+
+    let mut options = vec![format!("  method: {},", string_literal(&request.method))];
+    // browsers do not let scripts set cookies on a request, they send those of the page
+    let request_headers = sent_headers(request);
+    if !request_headers.is_empty() {
+        options.push("  headers: {".to_string());
+        options.extend(
+            request_headers
+                .iter()
+                .map(|(name, value)| format!("    {}: {},", string_literal(name), string_literal(value))),
+        );
+        options.push("  },".to_string());
+    }
+    if !request.cookies.is_empty() {
+        options.push("  credentials: \"include\",".to_string());
+    }
+    if let Some(body) = body {
+        options.push(format!("  body: {},", body));
+    }
+    code.push_str(&format!(
+        "const response = await fetch({}, {{\n{}\n}});\nconsole.log(response.status, await response.text());",
+        string_literal(&request.full_url()),
+        options.join("\n")
+    ));
+    code
+}
+
+fn python(request: &HttpRequest) -> String {
+    let mut arguments = vec![
+        format!("    {},", string_literal(&request.method)),
+        format!("    {},", string_literal(&request.full_url())),
+    ];
+    let request_headers = sent_headers(request);
+    if !request_headers.is_empty() {
+        arguments.push("    headers={".to_string());
+        arguments.extend(
+            request_headers
+                .iter()
+                .map(|(name, value)| format!("        {}: {},", string_literal(name), string_literal(value))),
+        );
+        arguments.push("    },".to_string());
+    }
+    if !request.cookies.is_empty() {
+        let cookies: Vec<String> = request
+            .cookies
+            .iter()
+            .map(|(name, value)| format!("{}: {}", string_literal(name), string_literal(value)))
+            .collect();
+        arguments.push(format!("    cookies={{{}}},", cookies.join(", ")));
+    }
+    match &request.body {
+        None => {}
+        Some(Body::Text(text)) => arguments.push(format!("    data={},", string_literal(text))),
+        Some(Body::UrlEncoded(pairs)) => {
+            let pairs: Vec<String> = pairs
+                .iter()
+                .map(|(name, value)| format!("({}, {})", string_literal(name), string_literal(value)))
+                .collect();
+            arguments.push(format!("    data=[{}],", pairs.join(", ")));
+        }
+        Some(Body::Multipart(parts)) => {
+            arguments.push("    files=[".to_string());
+            arguments.extend(parts.iter().map(|part| {
+                let value = match (&part.value, &part.content_type) {
+                    (PartValue::File(_), _) => {
+                        let name = string_literal(&file_name(part));
+                        format!("({}, open({}, \"rb\"))", name, name)
+                    }
+                    (PartValue::Text(text), Some(content_type)) => {
+                        format!("(None, {}, {})", string_literal(text), string_literal(content_type))
+                    }
+                    (PartValue::Text(text), None) => format!("(None, {})", string_literal(text)),
+                };
+                format!("        ({}, {}),", string_literal(&part.name), value)
+            }));
+            arguments.push("    ],".to_string());
+        }
+    }
+    format!(
+        "import requests\n\nresponse = requests.request(\n{}\n)\nprint(response.status_code, response.text)",
+        arguments.join("\n")
+    )
+}
+
+fn reqwest(request: &HttpRequest) -> String {
+    // `{:?}` of a str is a valid Rust string literal
+    let mut code = String::new();
+    if let Some(Body::Multipart(parts)) = &request.body {
+        code.push_str("let form = reqwest::multipart::Form::new()");
+        for part in parts {
+            code.push_str(&match (&part.value, &part.content_type) {
+                (PartValue::File(_), _) => format!("\n    .file({:?}, {:?})\n    .await?", part.name, file_name(part)),
+                (PartValue::Text(text), Some(content_type)) => format!(
+                    "\n    .part({:?}, reqwest::multipart::Part::text({:?}).mime_str({:?})?)",
+                    part.name, text, content_type
+                ),
+                (PartValue::Text(text), None) => format!("\n    .text({:?}, {:?})", part.name, text),
+            });
+        }
+        code.push_str(";\n");
+    }
+
+    let mut calls = vec![format!(
+        ".request(reqwest::Method::{}, {:?})",
+        request.method,
+        request.full_url()
+    )];
+    calls.extend(
+        headers_with_cookie(request)
+            .iter()
+            .map(|(name, value)| format!(".header({:?}, {:?})", name, value)),
+    );
+    match &request.body {
+        None => {}
+        Some(Body::Text(text)) => calls.push(format!(".body({:?})", text)),
+        Some(Body::UrlEncoded(pairs)) => {
+            let pairs: Vec<String> = pairs
+                .iter()
+                .map(|(name, value)| format!("({:?}, {:?})", name, value))
+                .collect();
+            calls.push(format!(".form(&[{}])", pairs.join(", ")));
+        }
+        Some(Body::Multipart(_)) => calls.push(".multipart(form)".to_string()),
+    }
+    calls.push(".send()".to_string());
+    calls.push(".await?;".to_string());
+    code.push_str(&format!(
+        "let response = reqwest::Client::new()\n    {}\nprintln!(\"{{}} {{}}\", response.status(), response.text().await?);",
+        calls.join("\n    ")
+    ));
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    fn json_request() -> HttpRequest {
+        let mut request = HttpRequest::new("post", "https://petstore.example.com/v1/pet?dry-run=true");
+        request.headers = vec![
+            ("Content-Type".to_string(), "application/json".to_string()),
+            ("Authorization".to_string(), "Bearer s3cr3t".to_string()),
+        ];
+        request.cookies = vec![("session".to_string(), "abc".to_string())];
+        request.body = Some(Body::Text("{\n  \"name\": \"Rex's\"\n}".to_string()));
+        request
+    }
+
+    fn form_request() -> HttpRequest {
+        let mut request = HttpRequest::new("POST", "https://petstore.example.com/v1/pet/1");
+        request.headers = vec![("Content-Type".to_string(), "multipart/form-data".to_string())];
+        request.body = Some(Body::Multipart(vec![
+            Part {
+                name: "name".to_string(),
+                value: PartValue::Text("Rex".to_string()),
+                content_type: None,
+            },
+            Part {
+                name: "meta".to_string(),
+                value: PartValue::Text("{}".to_string()),
+                content_type: Some("application/json".to_string()),
+            },
+        ]));
+        request
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_curl() {
+        assert_eq!(
+            r#"curl -X POST 'https://petstore.example.com/v1/pet?dry-run=true' \
+  -H 'Content-Type: application/json' \
+  -H 'Authorization: Bearer s3cr3t' \
+  -b 'session=abc' \
+  --data-raw '{
+  "name": "Rex'\''s"
+}'"#,
+            snippet(Language::Curl, &json_request())
+        );
+        assert_eq!(
+            r#"curl -X POST 'https://petstore.example.com/v1/pet/1' \
+  -F 'name=Rex' \
+  -F 'meta={};type=application/json'"#,
+            snippet(Language::Curl, &form_request())
+        );
+
+        let mut search = HttpRequest::new("GET", "https://petstore.example.com/v1/pet");
+        search.query = vec![("tags".to_string(), "a b".to_string())];
+        assert_eq!("curl 'https://petstore.example.com/v1/pet?tags=a%20b'", snippet(Language::Curl, &search));
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_httpie() {
+        assert_eq!(
+            r#"http POST 'https://petstore.example.com/v1/pet?dry-run=true' \
+  'Content-Type:application/json' \
+  'Authorization:Bearer s3cr3t' \
+  'Cookie:session=abc' \
+  --raw '{
+  "name": "Rex'\''s"
+}'"#,
+            snippet(Language::Httpie, &json_request())
+        );
+        let mut login = HttpRequest::new("POST", "https://petstore.example.com/v1/login");
+        login.body = Some(Body::UrlEncoded(vec![("user".to_string(), "me".to_string())]));
+        assert_eq!(
+            "http --form POST 'https://petstore.example.com/v1/login' \\\n  'user=me'",
+            snippet(Language::Httpie, &login)
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_fetch() {
+        assert_eq!(
+            r#"const response = await fetch("https://petstore.example.com/v1/pet?dry-run=true", {
+  method: "POST",
+  headers: {
+    "Content-Type": "application/json",
+    "Authorization": "Bearer s3cr3t",
+  },
+  credentials: "include",
+  body: "{\n  \"name\": \"Rex's\"\n}",
+});
+console.log(response.status, await response.text());"#,
+            snippet(Language::Fetch, &json_request())
+        );
+        assert_eq!(
+            r#"const body = new FormData();
+body.append("name", "Rex");
+body.append("meta", new Blob(["{}"], { type: "application/json" }));
+const response = await fetch("https://petstore.example.com/v1/pet/1", {
+  method: "POST",
+  body: body,
+});
+console.log(response.status, await response.text());"#,
+            snippet(Language::Fetch, &form_request())
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_python() {
+        assert_eq!(
+            r#"import requests
+
+response = requests.request(
+    "POST",
+    "https://petstore.example.com/v1/pet?dry-run=true",
+    headers={
+        "Content-Type": "application/json",
+        "Authorization": "Bearer s3cr3t",
+    },
+    cookies={"session": "abc"},
+    data="{\n  \"name\": \"Rex's\"\n}",
+)
+print(response.status_code, response.text)"#,
+            snippet(Language::Python, &json_request())
+        );
+        assert_eq!(
+            r#"import requests
+
+response = requests.request(
+    "POST",
+    "https://petstore.example.com/v1/pet/1",
+    files=[
+        ("name", (None, "Rex")),
+        ("meta", (None, "{}", "application/json")),
+    ],
+)
+print(response.status_code, response.text)"#,
+            snippet(Language::Python, &form_request())
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_reqwest() {
+        assert_eq!(
+            r#"let response = reqwest::Client::new()
+    .request(reqwest::Method::POST, "https://petstore.example.com/v1/pet?dry-run=true")
+    .header("Content-Type", "application/json")
+    .header("Authorization", "Bearer s3cr3t")
+    .header("Cookie", "session=abc")
+    .body("{\n  \"name\": \"Rex's\"\n}")
+    .send()
+    .await?;
+println!("{} {}", response.status(), response.text().await?);"#,
+            snippet(Language::Reqwest, &json_request())
+        );
+        assert_eq!(
+            r#"let form = reqwest::multipart::Form::new()
+    .text("name", "Rex")
+    .part("meta", reqwest::multipart::Part::text("{}").mime_str("application/json")?);
+let response = reqwest::Client::new()
+    .request(reqwest::Method::POST, "https://petstore.example.com/v1/pet/1")
+    .multipart(form)
+    .send()
+    .await?;
+println!("{} {}", response.status(), response.text().await?);"#,
+            snippet(Language::Reqwest, &form_request())
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_mask_secrets() {
+        let mut request = json_request();
+        request.query = vec![("api_key".to_string(), "k3y".to_string())];
+        let masked = mask_secrets(&request, &["s3cr3t".to_string(), "k3y".to_string(), String::new()]);
+
+        assert_eq!(
+            vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("Authorization".to_string(), "Bearer <secret>".to_string()),
+            ],
+            masked.headers
+        );
+        assert_eq!(vec![("api_key".to_string(), "<secret>".to_string())], masked.query);
+        assert_eq!(request.body, masked.body);
+    }
+}
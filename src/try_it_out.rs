@@ -0,0 +1,1726 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    js_sys, Document, Element, Event, HtmlElement, HtmlInputElement, HtmlOptionElement, HtmlSelectElement, HtmlTextAreaElement,
+};
+use super::auth::Auth;
+use super::curl;
+use super::dom::{self, Listeners};
+use super::form::{self, FieldKind, FieldValue, FormField};
+use super::har::Har;
+use super::history::{History, HistoryEntry};
+use super::http::{self, Body, HttpRequest, HttpResponse, Pairs, PartValue};
+use super::openapi::{
+    body_examples, check_response, coerce, is_json, validate, BodyExamples, Parameter, ParameterLocation, RequestMatch,
+    SampleOptions, Schema, Spec,
+};
+use super::params::{self, ParamValue, Serialization, Style};
+use super::snippets::{self, Language};
+use super::options::{RedocOptions, RedocTryItOutOptions};
+
+/// Id of the `<style>` tag shared by every instance on the page.
+pub const STYLE_ID: &str = "redoc-try-it-out-styles";
+const STYLES: &str = include_str!("styles.css");
+const DEFAULT_TRY_TEXT: &str = "Try it out";
+/// Value of the server picker option that sends requests to the custom base url.
+const CUSTOM_SERVER: &str = "custom";
+const HAR_TYPE: &str = "application/json";
+
+/// An operation box rendered by Redoc and the spec operation it shows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationTarget {
+    pub section_id: String,
+    pub method: String,
+    pub path: String,
+}
+
+impl OperationTarget {
+    fn from_element(element: &Element, spec: &Spec) -> Option<OperationTarget> {
+        let section_id = element.get_attribute("data-section-id")?;
+        let operation = spec.find_operation_by_section_id(&section_id)?;
+        Some(OperationTarget {
+            method: operation.method.to_uppercase(),
+            path: operation.path.to_string(),
+            section_id,
+        })
+    }
+}
+
+/// Redoc's `scrollYOffset`: a number of pixels, or the bottom of the element matching `selector`.
+#[derive(Debug, Clone, PartialEq)]
+enum ScrollOffset {
+    Pixels(f64),
+    Below(String),
+}
+
+impl ScrollOffset {
+    fn of(redoc_config: &RedocOptions) -> ScrollOffset {
+        let pixels = redoc_config
+            .scroll_y_offset
+            .as_deref()
+            .and_then(|offset| offset.trim().trim_end_matches("px").parse().ok());
+        match (pixels, &redoc_config.selector) {
+            (Some(pixels), _) => ScrollOffset::Pixels(pixels),
+            (None, Some(selector)) => ScrollOffset::Below(selector.clone()),
+            (None, None) => ScrollOffset::Pixels(0.0),
+        }
+    }
+
+    fn pixels(&self, document: &Document) -> Result<f64, JsValue> {
+        Ok(match self {
+            ScrollOffset::Pixels(pixels) => *pixels,
+            ScrollOffset::Below(selector) => document
+                .query_selector(selector)?
+                .map_or(0.0, |element| element.get_bounding_client_rect().bottom().max(0.0)),
+        })
+    }
+}
+
+struct Selection {
+    operation_box: Element,
+    button: Element,
+}
+
+/// Try-it-out panels injected next to every operation rendered by Redoc.
+pub struct TryItOut {
+    document: Document,
+    config: RedocTryItOutOptions,
+    spec: Rc<Spec>,
+    samples: SampleOptions,
+    /// Label servers with their variables expanded to the defaults, as Redoc does with
+    /// `expandDefaultServerVariables`.
+    expand_server_variables: bool,
+    /// Room left above a console scrolled to, for fixed headers.
+    scroll_offset: ScrollOffset,
+    auth: Rc<Auth>,
+    history: Option<History>,
+    /// Every request sent since the page loaded, oldest first, for HAR exports.
+    session: RefCell<Vec<HistoryEntry>>,
+    selection: RefCell<Option<Selection>>,
+    wrapper: HtmlElement,
+    /// Listeners of the elements added to the page, removed by `unmount`.
+    listeners: Listeners,
+    /// Listeners of the open console, removed when it closes.
+    console_listeners: Listeners,
+}
+
+impl TryItOut {
+    /// Inject a try button into every operation box below `container`.
+    pub fn mount(
+        document: &Document,
+        config: &RedocTryItOutOptions,
+        redoc_config: &RedocOptions,
+        container: &Element,
+        spec: Rc<Spec>,
+        auth: Rc<Auth>,
+    ) -> Result<Rc<TryItOut>, JsValue> {
+        dom::inject_style(document, STYLE_ID, STYLES, config.nonce.as_deref())?;
+
+        let wrapper = dom::create(document, "div", "try-it-out")?;
+        wrapper.set_id(&config.try_it_box_container_id);
+
+        let try_it_out = Rc::new(TryItOut {
+            document: document.clone(),
+            config: config.clone(),
+            spec,
+            samples: SampleOptions {
+                max_depth: redoc_config
+                    .generated_payload_samples_max_depth
+                    .unwrap_or(SampleOptions::default().max_depth),
+                only_required: redoc_config.only_required_in_samples.unwrap_or(false),
+            },
+            expand_server_variables: redoc_config.expand_default_server_variables.unwrap_or(false),
+            scroll_offset: ScrollOffset::of(redoc_config),
+            auth,
+            history: History::new(&config.history),
+            session: RefCell::default(),
+            selection: RefCell::new(None),
+            wrapper,
+            listeners: Listeners::default(),
+            console_listeners: Listeners::default(),
+        });
+
+        for operation_box in dom::query_all(container, &config.operation_box_selector)? {
+            if let Some(target) = OperationTarget::from_element(&operation_box, &try_it_out.spec) {
+                try_it_out.add_try_button(&operation_box, target)?;
+            }
+        }
+        try_it_out.add_curl_import(container)?;
+        try_it_out.add_har_box(container)?;
+
+        Ok(try_it_out)
+    }
+
+    /// A box after the API title that takes a curl command and opens the console of
+    /// the operation it calls, filled with its values.
+    fn add_curl_import(self: &Rc<Self>, container: &Element) -> Result<(), JsValue> {
+        let document = &self.document;
+        let import = dom::create(document, "details", "try-it-out-import")?;
+        let summary = dom::text(document, "summary", "", "Import a curl command")?;
+        import.append_child(&summary)?;
+        let command = dom::create(document, "textarea", "")?.dyn_into::<HtmlTextAreaElement>()?;
+        command.set_attribute("placeholder", "curl https://…")?;
+        command.set_attribute("spellcheck", "false")?;
+        import.append_child(&command)?;
+        let button = dom::text(document, "button", "try-it-out-import-btn", "Import")?;
+        button.set_attribute("type", "button")?;
+        import.append_child(&button)?;
+        let message = dom::create(document, "div", "try-it-out-error")?;
+        import.append_child(&message)?;
+        match container.query_selector("h1")? {
+            Some(title) => dom::insert_after(&title, &import)?,
+            None => {
+                container.prepend_with_node_1(&import)?;
+            }
+        }
+
+        let this = Rc::clone(self);
+        let container = container.clone();
+        self.listeners.add(&button, "click", move |_| match this.import_curl(&container, &command.value()) {
+            Ok(()) => message.set_text_content(None),
+            Err(e) => message.set_text_content(Some(&e.as_string().unwrap_or_else(|| format!("{:?}", e)))),
+        })?;
+        Ok(())
+    }
+
+    /// A box after the curl import that exports the requests sent so far as a HAR file and
+    /// lists the entries of an imported one, each of which can be replayed.
+    fn add_har_box(self: &Rc<Self>, container: &Element) -> Result<(), JsValue> {
+        let document = &self.document;
+        let har_box = dom::create(document, "details", "try-it-out-import try-it-out-har")?;
+        let summary = dom::text(document, "summary", "", "HAR files")?;
+        har_box.append_child(&summary)?;
+        let export = dom::text(document, "button", "", "Export session")?;
+        export.set_attribute("type", "button")?;
+        har_box.append_child(&export)?;
+        let file = dom::create(document, "input", "")?.dyn_into::<HtmlInputElement>()?;
+        file.set_type("file");
+        file.set_accept(".har,application/json");
+        har_box.append_child(&file)?;
+        let message = dom::create(document, "div", "try-it-out-error")?;
+        har_box.append_child(&message)?;
+        let entries = dom::create(document, "div", "try-it-out-har-entries")?;
+        har_box.append_child(&entries)?;
+        let after = container
+            .query_selector(".try-it-out-import")?
+            .or(container.query_selector("h1")?);
+        match after {
+            Some(element) => dom::insert_after(&element, &har_box)?,
+            None => {
+                container.prepend_with_node_1(&har_box)?;
+            }
+        }
+
+        let this = Rc::clone(self);
+        self.listeners.add(&export, "click", move |_| {
+            let har = Har::new(&this.session.borrow());
+            if let Err(e) = dom::download(&this.document, "session.har", HAR_TYPE, &har.to_json()) {
+                web_sys::console::error_1(&e);
+            }
+        })?;
+
+        // the imported entries are kept for the replay buttons
+        let imported: Rc<RefCell<Vec<HistoryEntry>>> = Rc::default();
+        let this = Rc::clone(self);
+        let list = entries.clone();
+        let error = message.clone();
+        let entries_of_file = Rc::clone(&imported);
+        let file_input = file.clone();
+        self.listeners.add(&file_input, "change", move |_| {
+            let Some(selected) = file.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            let this = Rc::clone(&this);
+            let list = list.clone();
+            let error = error.clone();
+            let imported = Rc::clone(&entries_of_file);
+            spawn_local(async move {
+                let read = async {
+                    let text = JsFuture::from(selected.text()).await?.as_string().unwrap_or_default();
+                    let har = Har::parse(&text).map_err(|e| JsValue::from_str(&e))?;
+                    *imported.borrow_mut() = har.log.entries.iter().map(HistoryEntry::from).collect();
+                    this.render_har_entries(&list, &imported.borrow())
+                };
+                match read.await {
+                    Ok(()) => error.set_text_content(None),
+                    Err(e) => error.set_text_content(Some(&e.as_string().unwrap_or_else(|| format!("{:?}", e)))),
+                }
+            });
+        })?;
+
+        let this = Rc::clone(self);
+        let container = container.clone();
+        self.listeners.add(&entries, "click", move |event: Event| {
+            let Some(index) = event
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .and_then(|button| button.get_attribute("data-har-entry"))
+                .and_then(|index| index.parse::<usize>().ok())
+            else {
+                return;
+            };
+            let Some(entry) = imported.borrow().get(index).cloned() else {
+                return;
+            };
+            let replayed = this
+                .open_request(&container, &entry.request.to_request())
+                .and_then(|(console, target)| this.restore(&console, &target, &entry));
+            match replayed {
+                Ok(()) => message.set_text_content(None),
+                Err(e) => message.set_text_content(Some(&e.as_string().unwrap_or_else(|| format!("{:?}", e)))),
+            }
+        })?;
+        Ok(())
+    }
+
+    fn render_har_entries(&self, list: &Element, entries: &[HistoryEntry]) -> Result<(), JsValue> {
+        list.set_inner_html("");
+        for (index, entry) in entries.iter().enumerate() {
+            let row = dom::create(&self.document, "div", "try-it-out-history-entry")?;
+            let summary = format!("{} {} → {}", entry.request.method, entry.request.url, outcome_summary(entry));
+            let text = dom::text(&self.document, "span", "", &summary)?;
+            row.append_child(&text)?;
+            let replay = dom::text(&self.document, "button", "", "Replay")?;
+            replay.set_attribute("type", "button")?;
+            replay.set_attribute("data-har-entry", &index.to_string())?;
+            row.append_child(&replay)?;
+            list.append_child(&row)?;
+        }
+        Ok(())
+    }
+
+    fn import_curl(self: &Rc<Self>, container: &Element, command: &str) -> Result<(), JsValue> {
+        let request = curl::parse_curl(command).map_err(|e| JsValue::from_str(&e))?;
+        self.open_request(container, &request)?;
+        Ok(())
+    }
+
+    /// Open the console of the operation `request` calls, filled with its values.
+    fn open_request(
+        self: &Rc<Self>,
+        container: &Element,
+        request: &HttpRequest,
+    ) -> Result<(Element, OperationTarget), JsValue> {
+        let found = self.spec.match_request(&request.method, &request.url).ok_or_else(|| {
+            JsValue::from_str(&format!("No operation matches {} {}", request.method, request.url))
+        })?;
+
+        for operation_box in dom::query_all(container, &self.config.operation_box_selector)? {
+            let Some(target) = OperationTarget::from_element(&operation_box, &self.spec) else {
+                continue;
+            };
+            if !target.method.eq_ignore_ascii_case(found.operation.method) || target.path != found.operation.path {
+                continue;
+            }
+            let Some(button) = operation_box.query_selector(".try-it-out-btn")? else {
+                continue;
+            };
+            let is_open = self
+                .selection
+                .borrow()
+                .as_ref()
+                .is_some_and(|selection| selection.operation_box == operation_box);
+            if !is_open {
+                self.toggle(&operation_box, &button, &target)?;
+            }
+            let console = self
+                .wrapper
+                .query_selector(".try-it-out-console")?
+                .ok_or_else(|| JsValue::from_str("should have a console"))?;
+            self.prefill(&console, &target, request, &found)?;
+            self.scroll_to_console()?;
+            return Ok((console, target));
+        }
+        Err(JsValue::from_str(&format!(
+            "{} {} is not shown on this page",
+            found.operation.method.to_uppercase(),
+            found.operation.path
+        )))
+    }
+
+    /// Fill the inputs of `console` with the values of an imported `request`.
+    fn prefill(
+        &self,
+        console: &Element,
+        target: &OperationTarget,
+        request: &HttpRequest,
+        found: &RequestMatch,
+    ) -> Result<(), JsValue> {
+        let picker = field::<HtmlSelectElement>(console, "server")?;
+        picker.set_value(CUSTOM_SERVER);
+        picker.dispatch_event(&Event::new("change")?)?;
+        field::<HtmlInputElement>(console, "base-url")?.set_value(&found.base_url);
+
+        let mut query = request.query.clone();
+        let mut headers = request.headers.clone();
+        let mut cookies = request.cookies.clone();
+        let parameters = self.parameters(target);
+        for input in dom::query_all(console, "[data-param]")? {
+            let Some((parameter, schema)) = input
+                .get_attribute("data-param")
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| parameters.get(index))
+            else {
+                continue;
+            };
+            let serialization = Serialization::of(parameter);
+            let mut values = match parameter.location {
+                ParameterLocation::Path => found
+                    .path_values
+                    .iter()
+                    .filter(|(name, _)| *name == parameter.name)
+                    .map(|(_, value)| value.clone())
+                    .collect(),
+                ParameterLocation::Query if serialization.style == Style::DeepObject => {
+                    let prefix = format!("{}[", parameter.name);
+                    let members: serde_json::Map<String, serde_json::Value> = take(&mut query, |name| name.starts_with(&prefix))
+                        .into_iter()
+                        .filter_map(|(name, value)| {
+                            let key = name.strip_prefix(&prefix)?.strip_suffix(']')?;
+                            Some((key.to_string(), serde_json::Value::String(value)))
+                        })
+                        .collect();
+                    if members.is_empty() {
+                        Vec::new()
+                    } else {
+                        vec![serde_json::to_string_pretty(&members).unwrap_or_default()]
+                    }
+                }
+                ParameterLocation::Query => values_of(take(&mut query, |name| name == parameter.name)),
+                ParameterLocation::Header => values_of(take(&mut headers, |name| name.eq_ignore_ascii_case(&parameter.name))),
+                ParameterLocation::Cookie => values_of(take(&mut cookies, |name| name == parameter.name)),
+            };
+            if schema.primary_type() == Some("array") && values.len() == 1 {
+                let delimiter = match serialization.style {
+                    Style::SpaceDelimited => ' ',
+                    Style::PipeDelimited => '|',
+                    _ => ',',
+                };
+                values = values[0].split(delimiter).map(str::to_string).collect();
+            }
+            if !values.is_empty() {
+                set_values(&input, &values)?;
+            }
+        }
+
+        // what no parameter takes is sent as it was
+        if !query.is_empty() {
+            let url = field::<HtmlInputElement>(console, "url")?;
+            let extra: Vec<String> = query
+                .iter()
+                .map(|(name, value)| format!("{}={}", http::encode_component(name), http::encode_component(value)))
+                .collect();
+            url.set_value(&format!("{}?{}", target.path, extra.join("&")));
+        }
+        let mut lines: Vec<String> = headers.iter().map(|(name, value)| format!("{}: {}", name, value)).collect();
+        if !cookies.is_empty() {
+            let cookies: Vec<String> = cookies.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+            lines.push(format!("Cookie: {}", cookies.join("; ")));
+        }
+        field::<HtmlTextAreaElement>(console, "headers")?.set_value(&lines.join("\n"));
+
+        match (&request.body, self.form_fields(target)) {
+            (Some(body), Some((_, fields))) => {
+                let sent: Pairs = match body {
+                    Body::Text(text) => http::form_decode(text),
+                    Body::UrlEncoded(pairs) => pairs.clone(),
+                    Body::Multipart(parts) => parts
+                        .iter()
+                        .filter_map(|part| match &part.value {
+                            PartValue::Text(text) => Some((part.name.clone(), text.clone())),
+                            PartValue::File(_) => None,
+                        })
+                        .collect(),
+                };
+                for input in dom::query_all(console, "[data-form-field]")? {
+                    let Some(field) = input
+                        .get_attribute("data-form-field")
+                        .and_then(|index| index.parse::<usize>().ok())
+                        .and_then(|index| fields.get(index))
+                    else {
+                        continue;
+                    };
+                    let values: Vec<String> = sent
+                        .iter()
+                        .filter(|(name, _)| *name == field.name)
+                        .map(|(_, value)| value.clone())
+                        .collect();
+                    if field.kind != FieldKind::File && !values.is_empty() {
+                        set_values(&input, &values)?;
+                    }
+                }
+            }
+            (Some(Body::Text(text)), None) => {
+                let text = serde_json::from_str::<serde_json::Value>(text)
+                    .ok()
+                    .and_then(|value| serde_json::to_string_pretty(&value).ok())
+                    .unwrap_or_else(|| text.clone());
+                field::<HtmlTextAreaElement>(console, "body")?.set_value(&text);
+            }
+            _ => {}
+        }
+
+        self.update_snippet(console, target)
+    }
+
+    fn add_try_button(self: &Rc<Self>, operation_box: &Element, target: OperationTarget) -> Result<(), JsValue> {
+        let try_btn = &self.config.try_btn;
+        let button = dom::text(
+            &self.document,
+            "button",
+            "try-it-out-btn",
+            try_btn.text.as_deref().unwrap_or(DEFAULT_TRY_TEXT),
+        )?;
+        button.set_attribute("type", "button")?;
+        if let Some(class_name) = &try_btn.class_name {
+            dom::add_classes(&button, class_name)?;
+        }
+
+        let sibling = match &try_btn.sibling_selector {
+            Some(selector) => operation_box.query_selector(selector)?,
+            None => operation_box.query_selector("h2")?,
+        };
+        match sibling {
+            Some(sibling) => dom::insert_after(&sibling, &button)?,
+            None => {
+                operation_box.prepend_with_node_1(&button)?;
+            }
+        }
+
+        let this = Rc::clone(self);
+        let operation_box = operation_box.clone();
+        let clicked = button.clone();
+        self.listeners.add(&button, "click", move |_| {
+            if let Err(e) = this.toggle(&operation_box, &clicked, &target) {
+                web_sys::console::error_1(&e);
+            }
+        })?;
+
+        Ok(())
+    }
+
+    /// Open the console for `target`, or close it if it is already open.
+    fn toggle(self: &Rc<Self>, operation_box: &Element, button: &Element, target: &OperationTarget) -> Result<(), JsValue> {
+        let previous = self.selection.borrow_mut().take();
+        if let Some(previous) = &previous {
+            self.deselect(previous)?;
+            if previous.operation_box == *operation_box {
+                return Ok(());
+            }
+        }
+
+        dom::add_classes(operation_box, &self.config.selected_operation_class)?;
+        if let Some(class_name) = &self.config.try_btn.selected_class_name {
+            dom::add_classes(button, class_name)?;
+        }
+
+        self.wrapper.set_inner_html("");
+        let console = self.render_console(target)?;
+        self.wrapper.append_child(&console)?;
+        dom::insert_after(button, &self.wrapper)?;
+
+        *self.selection.borrow_mut() = Some(Selection {
+            operation_box: operation_box.clone(),
+            button: button.clone(),
+        });
+        self.scroll_to_console()
+    }
+
+    fn scroll_to_console(&self) -> Result<(), JsValue> {
+        dom::scroll_to(&self.wrapper, self.scroll_offset.pixels(&self.document)?)
+    }
+
+    fn deselect(&self, selection: &Selection) -> Result<(), JsValue> {
+        dom::remove_classes(&selection.operation_box, &self.config.selected_operation_class)?;
+        if let Some(class_name) = &self.config.try_btn.selected_class_name {
+            dom::remove_classes(&selection.button, class_name)?;
+        }
+        self.wrapper.remove();
+        self.console_listeners.clear();
+        Ok(())
+    }
+
+    /// Remove everything `mount` added below `container` and the listeners, which also
+    /// frees the panels once the caller drops them.
+    pub fn unmount(&self, container: &Element) -> Result<(), JsValue> {
+        if let Some(selection) = self.selection.borrow_mut().take() {
+            self.deselect(&selection)?;
+        }
+        self.listeners.clear();
+        for element in dom::query_all(container, ".try-it-out-btn, .try-it-out-import")? {
+            element.remove();
+        }
+        Ok(())
+    }
+
+    fn render_console(self: &Rc<Self>, target: &OperationTarget) -> Result<HtmlElement, JsValue> {
+        let document = &self.document;
+        let console = dom::create(document, "form", "try-it-out-console")?;
+        console.set_attribute("data-section-id", &target.section_id)?;
+
+        let request_line = dom::create(document, "div", "try-it-out-request-line")?;
+        let method = dom::text(document, "span", "try-it-out-method", &target.method)?;
+        dom::add_classes(&method, &target.method.to_lowercase())?;
+        request_line.append_child(&method)?;
+        let url = dom::create(document, "input", "try-it-out-url")?;
+        url.set_attribute("name", "url")?;
+        url.set_attribute("value", &target.path)?;
+        request_line.append_child(&url)?;
+        console.append_child(&request_line)?;
+        let servers = self.render_servers(target)?;
+        console.append_child(&servers)?;
+
+        let parameters = self.parameters(target);
+        if !parameters.is_empty() {
+            let section = self.render_parameters(&parameters)?;
+            console.append_child(&section)?;
+        }
+
+        let examples = self
+            .spec
+            .find_operation_by_section_id(&target.section_id)
+            .and_then(|operation| operation.request_body(&self.spec))
+            .and_then(|body| body_examples(&self.spec, &body, self.samples));
+        let content_type = examples
+            .as_ref()
+            .map(|examples| format!("Content-Type: {}", examples.media_type))
+            .unwrap_or_default();
+        let headers = self.render_field("headers", "Headers", &content_type)?;
+        console.append_child(&headers)?;
+        let body = match self.form_fields(target) {
+            Some((_, fields)) => self.render_form(&fields)?,
+            None => self.render_body(examples)?,
+        };
+        console.append_child(&body)?;
+
+        let send = dom::text(document, "button", "try-it-out-send", "Send")?;
+        send.set_attribute("type", "submit")?;
+        console.append_child(&send)?;
+
+        let code = self.render_code()?;
+        console.append_child(&code)?;
+
+        let response = dom::create(document, "div", "try-it-out-response")?;
+        console.append_child(&response)?;
+        self.update_snippet(&console, target)?;
+        if self.history.is_some() {
+            let section = dom::create(document, "div", "try-it-out-history")?;
+            console.append_child(&section)?;
+            self.render_history(&console, target)?;
+
+            // the entries are re-rendered after every request, so their clicks are delegated
+            let this = Rc::clone(self);
+            let history_target = target.clone();
+            let form = console.clone();
+            self.console_listeners.add(&section, "click", move |event: Event| {
+                if let Err(e) = this.on_history_click(&form, &history_target, &event) {
+                    web_sys::console::error_1(&e);
+                }
+            })?;
+        }
+
+        for event in ["input", "change"] {
+            let this = Rc::clone(self);
+            let target_for_code = target.clone();
+            let form = console.clone();
+            self.console_listeners.add(&console, event, move |_| {
+                if let Err(e) = this.update_snippet(&form, &target_for_code) {
+                    web_sys::console::error_1(&e);
+                }
+            })?;
+        }
+
+        // every send rewrites the response area, so the Send anyway clicks are delegated to it
+        let output = console
+            .query_selector(".try-it-out-response")?
+            .ok_or_else(|| JsValue::from_str("should have a response area"))?;
+        let this = Rc::clone(self);
+        let send_target = target.clone();
+        let form = console.clone();
+        self.console_listeners.add(&output, "click", move |event: Event| {
+            let send_anyway = event
+                .target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .is_some_and(|target| target.class_list().contains("try-it-out-send-anyway"));
+            if !send_anyway {
+                return;
+            }
+            let this = Rc::clone(&this);
+            let target = send_target.clone();
+            let form = form.clone();
+            spawn_local(async move {
+                if let Err(e) = this.send(&form, &target, true).await {
+                    web_sys::console::error_1(&e);
+                }
+            });
+        })?;
+
+        let this = Rc::clone(self);
+        let target = target.clone();
+        let form = console.clone();
+        self.console_listeners.add(&console, "submit", move |event: Event| {
+            event.prevent_default();
+            let this = Rc::clone(&this);
+            let target = target.clone();
+            let form = form.clone();
+            spawn_local(async move {
+                if let Err(e) = this.send(&form, &target, false).await {
+                    web_sys::console::error_1(&e);
+                }
+            });
+        })?;
+
+        Ok(console)
+    }
+
+    /// A picker of the snippet language, whether to mask secrets and the snippet itself,
+    /// filled by `update_snippet`.
+    fn render_code(&self) -> Result<HtmlElement, JsValue> {
+        let document = &self.document;
+        let section = dom::create(document, "div", "try-it-out-code")?;
+        let toolbar = dom::create(document, "div", "try-it-out-code-toolbar")?;
+        let picker = dom::create(document, "select", "")?;
+        picker.set_attribute("name", "snippet-language")?;
+        for (index, language) in Language::ALL.iter().enumerate() {
+            let option = dom::text(document, "option", "", language.label())?;
+            option.set_attribute("value", &index.to_string())?;
+            picker.append_child(&option)?;
+        }
+        toolbar.append_child(&picker)?;
+
+        let mask = dom::create(document, "label", "")?;
+        let checkbox = dom::create(document, "input", "")?;
+        checkbox.set_attribute("type", "checkbox")?;
+        checkbox.set_attribute("name", "mask-secrets")?;
+        checkbox.set_attribute("checked", "")?;
+        mask.append_child(&checkbox)?;
+        let caption = dom::text(document, "span", "", "Mask secrets")?;
+        mask.append_child(&caption)?;
+        toolbar.append_child(&mask)?;
+
+        let copy = dom::text(document, "button", "try-it-out-copy", "Copy")?;
+        copy.set_attribute("type", "button")?;
+        toolbar.append_child(&copy)?;
+        section.append_child(&toolbar)?;
+
+        let snippet = dom::create(document, "pre", "try-it-out-snippet")?;
+        section.append_child(&snippet)?;
+
+        self.console_listeners.add(&copy, "click", move |_| {
+            let text = snippet.text_content().unwrap_or_default();
+            if let Some(window) = web_sys::window() {
+                // the promise only tells whether the page may write to the clipboard
+                let _ = window.navigator().clipboard().write_text(&text);
+            }
+        })?;
+
+        Ok(section)
+    }
+
+    /// Write the request composed in `console`, with its credentials, in the chosen language.
+    fn update_snippet(&self, console: &Element, target: &OperationTarget) -> Result<(), JsValue> {
+        let Some(output) = console.query_selector(".try-it-out-snippet")? else {
+            return Ok(());
+        };
+        let index: usize = field::<HtmlSelectElement>(console, "snippet-language")?
+            .value()
+            .parse()
+            .unwrap_or_default();
+        let language = Language::ALL.get(index).copied().unwrap_or(Language::Curl);
+        let mut request = self.read_request(console, target)?;
+        self.auth.apply(target, &mut request);
+        if field::<HtmlInputElement>(console, "mask-secrets")?.checked() {
+            request = snippets::mask_secrets(&request, &self.auth.secrets(target));
+        }
+        output.set_text_content(Some(&snippets::snippet(language, &request)));
+        Ok(())
+    }
+
+    /// Validate the inputs and send the request, unless there are problems and `force` is not set.
+    async fn send(self: &Rc<Self>, console: &Element, target: &OperationTarget, force: bool) -> Result<(), JsValue> {
+        let output = console
+            .query_selector(".try-it-out-response")?
+            .ok_or_else(|| JsValue::from_str("should have a response area"))?;
+        clear_problems(console)?;
+        let problems = self.validate_inputs(console, target)?;
+        if !problems.is_empty() {
+            show_problems(&self.document, &problems)?;
+            if !force {
+                return self.offer_send_anyway(&output, problems.len());
+            }
+        }
+
+        let mut request = self.read_request(console, target)?;
+        self.auth.refresh_expired().await;
+        self.auth.apply(target, &mut request);
+        output.set_inner_html("");
+        let pending = dom::text(&self.document, "div", "try-it-out-pending", "Sending…")?;
+        output.append_child(&pending)?;
+
+        let replaced_cookies = http::apply_cookies(&self.document, &request)?;
+        let sent_at = js_sys::Date::now();
+        let outcome = http::execute(&request)
+            .await
+            .map_err(|e| e.as_string().unwrap_or_else(|| format!("Request failed: {:?}", e)));
+        http::restore_cookies(&self.document, &replaced_cookies)?;
+        self.show_outcome(&output, target, outcome.as_ref())?;
+
+        let entry = HistoryEntry::redacted(
+            &request,
+            outcome.as_ref().map_err(String::clone),
+            &self.auth.secrets(target),
+            self.config.history.redact_secrets,
+            sent_at,
+        );
+        self.session.borrow_mut().push(entry.clone());
+        if let Some(history) = &self.history {
+            if let Err(e) = history.record(&history_key(target), entry) {
+                web_sys::console::warn_1(&JsValue::from_str(&e));
+            }
+            self.render_history(console, target)?;
+        }
+        Ok(())
+    }
+
+    fn show_outcome(&self, output: &Element, target: &OperationTarget, outcome: Result<&HttpResponse, &String>) -> Result<(), JsValue> {
+        let rendered = match outcome {
+            Ok(response) => self.render_response(response, target)?,
+            Err(e) => dom::text(&self.document, "div", "try-it-out-error", e)?,
+        };
+        output.set_inner_html("");
+        output.append_child(&rendered)?;
+        Ok(())
+    }
+
+    /// The requests sent from the console of `target`, newest first, each of which can be restored.
+    fn render_history(&self, console: &Element, target: &OperationTarget) -> Result<(), JsValue> {
+        let (Some(history), Some(section)) = (&self.history, console.query_selector(".try-it-out-history")?) else {
+            return Ok(());
+        };
+        section.set_inner_html("");
+        let entries = history.entries(&history_key(target));
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let document = &self.document;
+        let title = dom::text(document, "h5", "", "History")?;
+        section.append_child(&title)?;
+        for (index, entry) in entries.iter().enumerate() {
+            let sent_at = js_sys::Date::new(&JsValue::from_f64(entry.sent_at));
+            let summary = format!(
+                "{} {} {} → {}",
+                String::from(sent_at.to_locale_time_string("default")),
+                entry.request.method,
+                entry.request.url,
+                outcome_summary(entry)
+            );
+            let row = dom::create(document, "div", "try-it-out-history-entry")?;
+            let text = dom::text(document, "span", "", &summary)?;
+            row.append_child(&text)?;
+            for (action, label) in [("restore", "Restore"), ("har", "HAR")] {
+                let button = dom::text(document, "button", "", label)?;
+                button.set_attribute("type", "button")?;
+                button.set_attribute("data-history-action", action)?;
+                button.set_attribute("data-history-entry", &index.to_string())?;
+                row.append_child(&button)?;
+            }
+            section.append_child(&row)?;
+        }
+        let clear = dom::text(document, "button", "", "Clear history")?;
+        clear.set_attribute("type", "button")?;
+        clear.set_attribute("data-history-action", "clear")?;
+        section.append_child(&clear)?;
+        Ok(())
+    }
+
+    fn on_history_click(&self, console: &Element, target: &OperationTarget, event: &Event) -> Result<(), JsValue> {
+        let (Some(history), Some(button)) = (
+            &self.history,
+            event.target().and_then(|target| target.dyn_into::<Element>().ok()),
+        ) else {
+            return Ok(());
+        };
+        let key = history_key(target);
+        match button.get_attribute("data-history-action").as_deref() {
+            Some(action @ ("restore" | "har")) => {
+                let index: usize = button
+                    .get_attribute("data-history-entry")
+                    .and_then(|index| index.parse().ok())
+                    .unwrap_or_default();
+                let Some(entry) = history.entries(&key).into_iter().nth(index) else {
+                    return Ok(());
+                };
+                if action == "har" {
+                    dom::download(&self.document, "request.har", HAR_TYPE, &Har::new(&[entry]).to_json())?;
+                } else {
+                    self.restore(console, target, &entry)?;
+                }
+            }
+            Some("clear") => {
+                history.clear(&key).map_err(|e| JsValue::from_str(&e))?;
+                self.render_history(console, target)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Fill `console` with the request of `entry` and show what it received.
+    fn restore(&self, console: &Element, target: &OperationTarget, entry: &HistoryEntry) -> Result<(), JsValue> {
+        let request = entry.request.to_request();
+        let operation = self
+            .spec
+            .find_operation_by_section_id(&target.section_id)
+            .ok_or_else(|| JsValue::from_str("should find the operation of the console"))?;
+        if let Some(found) = self.spec.match_url(operation, &request.url) {
+            self.prefill(console, target, &request, &found)?;
+        }
+        let output = console
+            .query_selector(".try-it-out-response")?
+            .ok_or_else(|| JsValue::from_str("should have a response area"))?;
+        let response = entry.response.as_ref().map(HttpResponse::from);
+        match (&response, &entry.error) {
+            (Some(response), _) => self.show_outcome(&output, target, Ok(response)),
+            (None, Some(error)) => self.show_outcome(&output, target, Err(error)),
+            (None, None) => {
+                output.set_inner_html("");
+                Ok(())
+            }
+        }
+    }
+
+    fn offer_send_anyway(&self, output: &Element, count: usize) -> Result<(), JsValue> {
+        output.set_inner_html("");
+        let warning = dom::text(
+            &self.document,
+            "div",
+            "try-it-out-warning",
+            &format!("{} input{} do not match the spec. ", count, if count == 1 { "" } else { "s" }),
+        )?;
+        let button = dom::text(&self.document, "button", "try-it-out-send-anyway", "Send anyway")?;
+        button.set_attribute("type", "button")?;
+        warning.append_child(&button)?;
+        output.append_child(&warning)?;
+        Ok(())
+    }
+
+    /// Inputs whose values do not match their schema, with what is wrong with them.
+    fn validate_inputs(&self, console: &Element, target: &OperationTarget) -> Result<Vec<(Element, Vec<String>)>, JsValue> {
+        let mut problems = Vec::new();
+
+        let parameters = self.parameters(target);
+        for input in dom::query_all(console, "[data-param]")? {
+            let Some((parameter, schema)) = input
+                .get_attribute("data-param")
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| parameters.get(index))
+            else {
+                continue;
+            };
+            let messages = match read_parameter(&input, parameter, schema) {
+                None if parameter.required => vec!["is required".to_string()],
+                None => Vec::new(),
+                Some(value) => violations(&self.spec, schema, &self.parameter_json(parameter, schema, &value)),
+            };
+            if !messages.is_empty() {
+                problems.push((input, messages));
+            }
+        }
+
+        if let Some((_, fields)) = self.form_fields(target) {
+            for input in dom::query_all(console, "[data-form-field]")? {
+                let Some(field) = input
+                    .get_attribute("data-form-field")
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .and_then(|index| fields.get(index))
+                else {
+                    continue;
+                };
+                let messages = match read_form_input(&input, field) {
+                    Some(FieldValue::Text(texts)) if texts.iter().any(|text| !text.trim().is_empty()) => {
+                        let texts: Vec<&String> = texts.iter().filter(|text| !text.trim().is_empty()).collect();
+                        let value = match (&field.schema.items, field.multiple) {
+                            (Some(items), true) => {
+                                serde_json::Value::Array(texts.iter().map(|text| coerce(&self.spec, items, text)).collect())
+                            }
+                            _ => coerce(&self.spec, &field.schema, texts[0]),
+                        };
+                        violations(&self.spec, &field.schema, &value)
+                    }
+                    Some(FieldValue::Files(files)) if !files.is_empty() => Vec::new(),
+                    _ if field.required => vec!["is required".to_string()],
+                    _ => Vec::new(),
+                };
+                if !messages.is_empty() {
+                    problems.push((input, messages));
+                }
+            }
+        } else if let Some(editor) = console.query_selector("[name=\"body\"]")? {
+            let text = field::<HtmlTextAreaElement>(console, "body")?.value();
+            let operation = self.spec.find_operation_by_section_id(&target.section_id);
+            let body = operation.and_then(|operation| operation.request_body(&self.spec));
+            let media_type = body
+                .as_ref()
+                .and_then(|body| body_examples(&self.spec, body, self.samples))
+                .map(|examples| examples.media_type);
+            let schema = body
+                .as_ref()
+                .zip(media_type.as_ref())
+                .and_then(|(body, media_type)| body.content.get(media_type))
+                .and_then(|media| media.schema.clone());
+            let required = body.as_ref().is_some_and(|body| body.required);
+
+            let messages = if text.trim().is_empty() {
+                if required {
+                    vec!["is required".to_string()]
+                } else {
+                    Vec::new()
+                }
+            } else if media_type.as_deref().is_some_and(is_json) {
+                match (serde_json::from_str::<serde_json::Value>(&text), &schema) {
+                    (Err(e), _) => vec![format!("is not valid JSON: {}", e)],
+                    (Ok(value), Some(schema)) => violations(&self.spec, schema, &value),
+                    (Ok(_), None) => Vec::new(),
+                }
+            } else {
+                Vec::new()
+            };
+            if !messages.is_empty() {
+                problems.push((editor, messages));
+            }
+        }
+        Ok(problems)
+    }
+
+    /// A parameter value as the JSON value its schema describes.
+    fn parameter_json(&self, parameter: &Parameter, schema: &Schema, value: &ParamValue) -> serde_json::Value {
+        let property = |name: &str| schema.properties.get(name).cloned().unwrap_or_default();
+        match value {
+            ParamValue::Primitive(text) if !parameter.content.is_empty() => {
+                serde_json::from_str(text).unwrap_or_else(|_| serde_json::Value::String(text.clone()))
+            }
+            ParamValue::Primitive(text) => coerce(&self.spec, schema, text),
+            ParamValue::Array(items) => {
+                let item = schema.items.as_deref().cloned().unwrap_or_default();
+                serde_json::Value::Array(items.iter().map(|text| coerce(&self.spec, &item, text)).collect())
+            }
+            ParamValue::Object(members) => serde_json::Value::Object(
+                members
+                    .iter()
+                    .map(|(name, text)| (name.clone(), coerce(&self.spec, &property(name), text)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn read_request(&self, console: &Element, target: &OperationTarget) -> Result<HttpRequest, JsValue> {
+        let path = field::<HtmlInputElement>(console, "url")?.value();
+        let url = self.spec.operation_url(&self.read_server_url(console, target)?, path.trim());
+        let (headers, cookies) = parse_headers(&field::<HtmlTextAreaElement>(console, "headers")?.value());
+        let mut request = HttpRequest::new(&target.method, &url);
+        request.headers = headers;
+        request.cookies = cookies;
+        self.apply_parameters(console, target, &mut request)?;
+        if let Some((media_type, fields)) = self.form_fields(target) {
+            let values = read_form(console, &fields)?;
+            request.body = Some(form::form_body(&media_type, &fields, &values));
+        } else {
+            let body = field::<HtmlTextAreaElement>(console, "body")?.value();
+            if !body.trim().is_empty() {
+                request.body = Some(Body::Text(body));
+            }
+        }
+        Ok(request)
+    }
+
+    /// The url of the chosen server with the variables entered, or the custom base url.
+    fn read_server_url(&self, console: &Element, target: &OperationTarget) -> Result<String, JsValue> {
+        let choice = field::<HtmlSelectElement>(console, "server")?.value();
+        if choice == CUSTOM_SERVER {
+            return Ok(field::<HtmlInputElement>(console, "base-url")?.value());
+        }
+        let Some(operation) = self.spec.find_operation_by_section_id(&target.section_id) else {
+            return Ok(String::new());
+        };
+        let servers = operation.servers(&self.spec);
+        let index: usize = choice.parse().unwrap_or_default();
+        let Some(server) = servers.get(index) else {
+            return Ok(String::new());
+        };
+        let mut values = Vec::new();
+        for input in dom::query_all(console, &format!("[data-server=\"{}\"] [data-server-variable]", index))? {
+            let Some(name) = input.get_attribute("data-server-variable") else {
+                continue;
+            };
+            let value = match input.dyn_ref::<HtmlSelectElement>() {
+                Some(select) => select.value(),
+                None => input.dyn_into::<HtmlInputElement>()?.value(),
+            };
+            values.push((name, value));
+        }
+        Ok(server.expand(&values))
+    }
+
+    /// The parameters of `target` with their dereferenced schemas.
+    fn parameters(&self, target: &OperationTarget) -> Vec<(Parameter, Schema)> {
+        let Some(operation) = self.spec.find_operation_by_section_id(&target.section_id) else {
+            return Vec::new();
+        };
+        operation
+            .parameters(&self.spec)
+            .into_iter()
+            .map(|parameter| {
+                let schema = parameter
+                    .schema
+                    .as_ref()
+                    .or_else(|| parameter.content.values().next().and_then(|media| media.schema.as_ref()))
+                    .map(|schema| self.spec.dereference_schema(schema))
+                    .unwrap_or_default();
+                (parameter.into_owned(), schema)
+            })
+            .collect()
+    }
+
+    /// A picker of the servers of `target`, with inputs for the variables of the chosen one
+    /// and a custom base url, preselected when `baseUrl` is configured.
+    fn render_servers(&self, target: &OperationTarget) -> Result<HtmlElement, JsValue> {
+        let document = &self.document;
+        let section = dom::create(document, "div", "try-it-out-servers")?;
+        let servers = self
+            .spec
+            .find_operation_by_section_id(&target.section_id)
+            .map(|operation| operation.servers(&self.spec).into_owned())
+            .unwrap_or_default();
+
+        let row = dom::create(document, "label", "try-it-out-field")?;
+        let caption = dom::text(document, "span", "try-it-out-label", "Server")?;
+        row.append_child(&caption)?;
+        let picker = dom::create(document, "select", "")?.dyn_into::<HtmlSelectElement>()?;
+        picker.set_attribute("name", "server")?;
+        for (index, server) in servers.iter().enumerate() {
+            let url = if self.expand_server_variables {
+                server.expand(&[])
+            } else {
+                server.url.clone()
+            };
+            let label = match &server.description {
+                Some(description) => format!("{} - {}", url, description),
+                None => url,
+            };
+            let option = dom::text(document, "option", "", &label)?;
+            option.set_attribute("value", &index.to_string())?;
+            picker.append_child(&option)?;
+        }
+        let custom = dom::text(document, "option", "", "Custom base URL")?;
+        custom.set_attribute("value", CUSTOM_SERVER)?;
+        picker.append_child(&custom)?;
+        row.append_child(&picker)?;
+        section.append_child(&row)?;
+
+        let base_url = dom::create(document, "input", "try-it-out-base-url")?.dyn_into::<HtmlInputElement>()?;
+        base_url.set_attribute("name", "base-url")?;
+        base_url.set_attribute("placeholder", "http://localhost:8080")?;
+        section.append_child(&base_url)?;
+
+        let mut groups = Vec::new();
+        for (index, server) in servers.iter().enumerate() {
+            let group = dom::create(document, "div", "try-it-out-server-variables")?;
+            group.set_attribute("data-server", &index.to_string())?;
+            for (name, variable) in &server.variables {
+                let row = dom::create(document, "label", "try-it-out-field")?;
+                let caption = dom::text(document, "span", "try-it-out-label", name)?;
+                if let Some(description) = &variable.description {
+                    caption.set_title(description);
+                }
+                row.append_child(&caption)?;
+                let input = if variable.enumeration.is_empty() {
+                    let input = dom::create(document, "input", "")?;
+                    input.set_attribute("value", &variable.default)?;
+                    input
+                } else {
+                    let select = dom::create(document, "select", "")?;
+                    for value in &variable.enumeration {
+                        let option = dom::text(document, "option", "", value)?;
+                        option.set_attribute("value", value)?;
+                        if *value == variable.default {
+                            option.set_attribute("selected", "")?;
+                        }
+                        select.append_child(&option)?;
+                    }
+                    select
+                };
+                input.set_attribute("data-server-variable", name)?;
+                row.append_child(&input)?;
+                group.append_child(&row)?;
+            }
+            section.append_child(&group)?;
+            groups.push(group);
+        }
+
+        if let Some(configured) = &self.config.base_url {
+            picker.set_value(CUSTOM_SERVER);
+            base_url.set_value(configured);
+        }
+        let show_chosen = move |picker: &HtmlSelectElement| {
+            let choice = picker.value();
+            base_url.set_hidden(choice != CUSTOM_SERVER);
+            for group in &groups {
+                group.set_hidden(group.get_attribute("data-server").as_deref() != Some(choice.as_str()));
+            }
+        };
+        show_chosen(&picker);
+
+        let chosen = picker.clone();
+        self.console_listeners.add(&picker, "change", move |_| show_chosen(&chosen))?;
+
+        Ok(section)
+    }
+
+    /// One input per parameter; `data-param` holds the index of the parameter.
+    fn render_parameters(&self, parameters: &[(Parameter, Schema)]) -> Result<HtmlElement, JsValue> {
+        let document = &self.document;
+        let section = dom::create(document, "div", "try-it-out-parameters")?;
+        for (index, (parameter, schema)) in parameters.iter().enumerate() {
+            let row = dom::create(document, "label", "try-it-out-field")?;
+            let caption = format!(
+                "{}{} ({})",
+                parameter.name,
+                if parameter.required { " *" } else { "" },
+                location_name(parameter.location)
+            );
+            let caption = dom::text(document, "span", "try-it-out-label", &caption)?;
+            if let Some(description) = &parameter.description {
+                caption.set_title(description);
+            }
+            row.append_child(&caption)?;
+
+            let initial = self.parameter_example(parameter, schema);
+            let is_array = schema.primary_type() == Some("array");
+            let item = match (&schema.items, is_array) {
+                (Some(items), true) => items.as_ref(),
+                _ => schema,
+            };
+            let mut options: Vec<String> = item.enumeration.iter().flatten().map(value_text).collect();
+            if options.is_empty() && item.primary_type() == Some("boolean") {
+                options = vec!["true".to_string(), "false".to_string()];
+            }
+            let input = if !options.is_empty() {
+                let select = dom::create(document, "select", "")?;
+                if is_array {
+                    select.set_attribute("multiple", "")?;
+                } else {
+                    let empty = dom::text(document, "option", "", "")?;
+                    select.append_child(&empty)?;
+                }
+                let selected = example_lines(initial.as_ref());
+                for value in &options {
+                    let option = dom::text(document, "option", "", value)?;
+                    option.set_attribute("value", value)?;
+                    if selected.contains(value) {
+                        option.set_attribute("selected", "")?;
+                    }
+                    select.append_child(&option)?;
+                }
+                select
+            } else if is_array || is_object(schema) {
+                let textarea = dom::create(document, "textarea", "")?;
+                if is_array {
+                    textarea.set_attribute("placeholder", "one value per line")?;
+                    textarea.set_text_content(Some(&example_lines(initial.as_ref()).join("\n")));
+                } else if let Some(initial) = &initial {
+                    textarea.set_text_content(Some(&serde_json::to_string_pretty(initial).unwrap_or_default()));
+                }
+                textarea
+            } else {
+                let input = dom::create(document, "input", "")?;
+                if let Some(initial) = &initial {
+                    input.set_attribute("value", &value_text(initial))?;
+                }
+                if let Some(schema_type) = schema.primary_type() {
+                    input.set_attribute("placeholder", schema_type)?;
+                }
+                input
+            };
+            input.set_attribute("data-param", &index.to_string())?;
+            row.append_child(&input)?;
+            section.append_child(&row)?;
+        }
+        Ok(section)
+    }
+
+    fn parameter_example(&self, parameter: &Parameter, schema: &Schema) -> Option<serde_json::Value> {
+        parameter
+            .example
+            .clone()
+            .or_else(|| {
+                parameter
+                    .examples
+                    .values()
+                    .next()
+                    .and_then(|example| self.spec.resolve(example))
+                    .and_then(|example| example.value.clone())
+            })
+            .or_else(|| schema.example.clone())
+            .or_else(|| schema.default.clone())
+    }
+
+    /// Write the values entered for the parameters of `target` into `request`.
+    fn apply_parameters(&self, console: &Element, target: &OperationTarget, request: &mut HttpRequest) -> Result<(), JsValue> {
+        let parameters = self.parameters(target);
+        let mut path_values = Vec::new();
+        let mut query = Vec::new();
+        for input in dom::query_all(console, "[data-param]")? {
+            let Some((parameter, schema)) = input
+                .get_attribute("data-param")
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| parameters.get(index))
+            else {
+                continue;
+            };
+            let Some(value) = read_parameter(&input, parameter, schema) else {
+                continue;
+            };
+            let serialization = Serialization::of(parameter);
+            match parameter.location {
+                ParameterLocation::Path => {
+                    path_values.push((parameter.name.clone(), params::path_value(&parameter.name, &value, serialization)))
+                }
+                ParameterLocation::Query => query.extend(params::query_pieces(&parameter.name, &value, serialization)),
+                ParameterLocation::Header => request.set_header(&parameter.name, &params::header_value(&value, serialization)),
+                ParameterLocation::Cookie => request
+                    .cookies
+                    .extend(params::cookie_pairs(&parameter.name, &value, serialization)),
+            }
+        }
+
+        request.url = params::expand_path(&request.url, &path_values);
+        if !query.is_empty() {
+            let separator = if request.url.contains('?') { '&' } else { '?' };
+            request.url = format!("{}{}{}", request.url, separator, query.join("&"));
+        }
+        Ok(())
+    }
+
+    /// The form fields of `target`, when its request body is sent as a form.
+    fn form_fields(&self, target: &OperationTarget) -> Option<(String, Vec<FormField>)> {
+        let operation = self.spec.find_operation_by_section_id(&target.section_id)?;
+        let body = operation.request_body(&self.spec)?;
+        let media_type = body_examples(&self.spec, &body, self.samples)?.media_type;
+        if !form::is_form(&media_type) {
+            return None;
+        }
+        let fields = form::form_fields(&self.spec, &body.content[&media_type], self.samples);
+        Some((media_type, fields))
+    }
+
+    fn render_response(&self, response: &HttpResponse, target: &OperationTarget) -> Result<HtmlElement, JsValue> {
+        let document = &self.document;
+        let rendered = dom::create(document, "div", "")?;
+
+        let status = dom::text(
+            document,
+            "div",
+            "try-it-out-status",
+            &format!("{} {} · {:.0} ms", response.status, response.status_text, response.duration_ms),
+        )?;
+        let status_class = if response.status < 400 { "success" } else { "failure" };
+        dom::add_classes(&status, status_class)?;
+        rendered.append_child(&status)?;
+
+        let headers = response
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let headers = dom::text(document, "pre", "try-it-out-headers", &headers)?;
+        rendered.append_child(&headers)?;
+        let body = dom::text(document, "pre", "try-it-out-body", &response.pretty_body())?;
+        rendered.append_child(&body)?;
+        if let Some(report) = self.render_report(response, target)? {
+            rendered.insert_before(&report, status.next_sibling().as_ref())?;
+        }
+        Ok(rendered)
+    }
+
+    /// Whether the response is the one the spec documents, and what differs if it is not.
+    fn render_report(&self, response: &HttpResponse, target: &OperationTarget) -> Result<Option<HtmlElement>, JsValue> {
+        let Some(operation) = self.spec.find_operation_by_section_id(&target.section_id) else {
+            return Ok(None);
+        };
+        let report = check_response(&self.spec, &operation, response.status, &response.headers, &response.body);
+        let summary = match &report.documented {
+            None => format!("Status {} is not documented", response.status),
+            Some(documented) if report.violations.is_empty() => format!("Matches the documented {} response", documented),
+            Some(documented) => format!("Does not match the documented {} response", documented),
+        };
+        let rendered = dom::text(&self.document, "div", "try-it-out-report", &summary)?;
+        dom::add_classes(&rendered, if report.conforms() { "success" } else { "failure" })?;
+        if !report.violations.is_empty() {
+            let list = dom::create(&self.document, "ul", "")?;
+            for violation in &report.violations {
+                let item = dom::text(&self.document, "li", "", &violation.to_string())?;
+                list.append_child(&item)?;
+            }
+            rendered.append_child(&list)?;
+        }
+        Ok(Some(rendered))
+    }
+
+    /// The body editor, prefilled with the first example and with a picker when there are several.
+    fn render_body(&self, examples: Option<BodyExamples>) -> Result<HtmlElement, JsValue> {
+        let examples = examples.map(|examples| examples.examples).unwrap_or_default();
+        let initial = examples.first().map(|example| example.text.as_str()).unwrap_or_default();
+        let body = self.render_field("body", "Body", initial)?;
+        let editor = field::<HtmlTextAreaElement>(&body, "body")?;
+        dom::add_classes(&editor, "try-it-out-editor")?;
+        editor.set_attribute("spellcheck", "false")?;
+        if examples.len() < 2 {
+            return Ok(body);
+        }
+
+        let picker = dom::create(&self.document, "select", "try-it-out-examples")?.dyn_into::<HtmlSelectElement>()?;
+        picker.set_attribute("name", "example")?;
+        for (index, example) in examples.iter().enumerate() {
+            let option = dom::text(&self.document, "option", "", &example.label)?;
+            option.set_attribute("value", &index.to_string())?;
+            picker.append_child(&option)?;
+        }
+        body.insert_before(&picker, Some(&editor))?;
+
+        let selected = picker.clone();
+        self.console_listeners.add(&picker, "change", move |_| {
+            let index: usize = selected.value().parse().unwrap_or_default();
+            if let Some(example) = examples.get(index) {
+                editor.set_value(&example.text);
+            }
+        })?;
+
+        Ok(body)
+    }
+
+    /// One input per form field; `data-form-field` holds the index of the field.
+    fn render_form(&self, fields: &[FormField]) -> Result<HtmlElement, JsValue> {
+        let document = &self.document;
+        let form = dom::create(document, "div", "try-it-out-form")?;
+        for (index, field) in fields.iter().enumerate() {
+            let row = dom::create(document, "label", "try-it-out-field")?;
+            let caption = if field.required {
+                format!("{} *", field.name)
+            } else {
+                field.name.clone()
+            };
+            let caption = dom::text(document, "span", "try-it-out-label", &caption)?;
+            if let Some(description) = &field.description {
+                caption.set_title(description);
+            }
+            row.append_child(&caption)?;
+
+            let input = match field.kind {
+                FieldKind::File => {
+                    let input = dom::create(document, "input", "")?;
+                    input.set_attribute("type", "file")?;
+                    input
+                }
+                FieldKind::Boolean => self.render_select(&["true".to_string(), "false".to_string()], field)?,
+                _ if !field.options.is_empty() => self.render_select(&field.options, field)?,
+                FieldKind::Json => {
+                    let input = dom::create(document, "textarea", "")?;
+                    input.set_text_content(field.sample.first().map(String::as_str));
+                    input
+                }
+                FieldKind::Text if field.multiple => {
+                    let input = dom::create(document, "textarea", "")?;
+                    input.set_attribute("placeholder", "one value per line")?;
+                    input.set_text_content(Some(&field.sample.join("\n")));
+                    input
+                }
+                FieldKind::Text => {
+                    let input = dom::create(document, "input", "")?;
+                    input.set_attribute("value", field.sample.first().map(String::as_str).unwrap_or_default())?;
+                    input
+                }
+            };
+            if field.multiple {
+                input.set_attribute("multiple", "")?;
+            }
+            input.set_attribute("data-form-field", &index.to_string())?;
+            row.append_child(&input)?;
+            form.append_child(&row)?;
+        }
+        Ok(form)
+    }
+
+    fn render_select(&self, options: &[String], field: &FormField) -> Result<HtmlElement, JsValue> {
+        let select = dom::create(&self.document, "select", "")?;
+        if !field.multiple && !field.required {
+            let empty = dom::text(&self.document, "option", "", "")?;
+            select.append_child(&empty)?;
+        }
+        for value in options {
+            let option = dom::text(&self.document, "option", "", value)?;
+            option.set_attribute("value", value)?;
+            if field.sample.contains(value) {
+                option.set_attribute("selected", "")?;
+            }
+            select.append_child(&option)?;
+        }
+        Ok(select)
+    }
+
+    fn render_field(&self, name: &str, label: &str, value: &str) -> Result<HtmlElement, JsValue> {
+        let field = dom::create(&self.document, "label", "try-it-out-field")?;
+        let caption = dom::text(&self.document, "span", "try-it-out-label", label)?;
+        field.append_child(&caption)?;
+        let textarea = dom::create(&self.document, "textarea", "")?;
+        textarea.set_attribute("name", name)?;
+        textarea.set_text_content(Some(value));
+        field.append_child(&textarea)?;
+        Ok(field)
+    }
+}
+
+fn field<T: JsCast>(console: &Element, name: &str) -> Result<T, JsValue> {
+    console
+        .query_selector(&format!("[name=\"{}\"]", name))?
+        .ok_or_else(|| JsValue::from_str(&format!("should have a {} field", name)))?
+        .dyn_into::<T>()
+        .map_err(|_| JsValue::from_str(&format!("unexpected {} field", name)))
+}
+
+/// The value entered for a parameter, or `None` when it was left empty.
+fn read_parameter(input: &Element, parameter: &Parameter, schema: &Schema) -> Option<ParamValue> {
+    let texts: Vec<String> = if let Some(select) = input.dyn_ref::<HtmlSelectElement>() {
+        let selected = select.selected_options();
+        (0..selected.length())
+            .filter_map(|i| selected.item(i))
+            .filter_map(|option| option.dyn_into::<HtmlOptionElement>().ok())
+            .map(|option| option.value())
+            .collect()
+    } else if let Some(textarea) = input.dyn_ref::<HtmlTextAreaElement>() {
+        vec![textarea.value()]
+    } else {
+        vec![input.dyn_ref::<HtmlInputElement>()?.value()]
+    };
+    let texts: Vec<String> = texts.into_iter().filter(|text| !text.trim().is_empty()).collect();
+    if texts.is_empty() {
+        return None;
+    }
+
+    if !parameter.content.is_empty() {
+        return Some(ParamValue::Primitive(texts.concat()));
+    }
+    if schema.primary_type() == Some("array") {
+        let items = if input.dyn_ref::<HtmlTextAreaElement>().is_some() {
+            texts
+                .concat()
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        } else {
+            texts
+        };
+        return Some(ParamValue::Array(items));
+    }
+    if is_object(schema) {
+        let text = texts.concat();
+        return Some(match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text) {
+            Ok(members) => ParamValue::Object(members.iter().map(|(key, value)| (key.clone(), value_text(value))).collect()),
+            Err(_) => ParamValue::Primitive(text),
+        });
+    }
+    Some(ParamValue::Primitive(texts.concat()))
+}
+
+fn is_object(schema: &Schema) -> bool {
+    schema.primary_type() == Some("object") || (schema.primary_type().is_none() && !schema.properties.is_empty())
+}
+
+fn location_name(location: ParameterLocation) -> &'static str {
+    match location {
+        ParameterLocation::Path => "path",
+        ParameterLocation::Query => "query",
+        ParameterLocation::Header => "header",
+        ParameterLocation::Cookie => "cookie",
+    }
+}
+
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// An example as lines of an array input.
+fn example_lines(example: Option<&serde_json::Value>) -> Vec<String> {
+    match example {
+        Some(serde_json::Value::Array(items)) => items.iter().map(value_text).collect(),
+        Some(value) => vec![value_text(value)],
+        None => Vec::new(),
+    }
+}
+
+/// The status of the response of `entry`, or why there was none.
+fn outcome_summary(entry: &HistoryEntry) -> String {
+    match (&entry.response, &entry.error) {
+        (Some(response), _) => format!("{} {}", response.status, response.status_text),
+        (None, Some(error)) => error.clone(),
+        (None, None) => String::new(),
+    }
+}
+
+/// Operations are kept apart by method and path, which stay the same when the spec changes.
+fn history_key(target: &OperationTarget) -> String {
+    format!("{} {}", target.method, target.path)
+}
+
+/// Remove the pairs whose name is `wanted` from `pairs` and return them.
+fn take(pairs: &mut Pairs, wanted: impl Fn(&str) -> bool) -> Pairs {
+    let (taken, kept) = pairs.drain(..).partition(|(name, _)| wanted(name));
+    *pairs = kept;
+    taken
+}
+
+fn values_of(pairs: Pairs) -> Vec<String> {
+    pairs.into_iter().map(|(_, value)| value).collect()
+}
+
+/// Show `values` in a parameter or form field input.
+fn set_values(input: &Element, values: &[String]) -> Result<(), JsValue> {
+    if let Some(select) = input.dyn_ref::<HtmlSelectElement>() {
+        for i in 0..select.length() {
+            if let Some(option) = select.item(i).and_then(|option| option.dyn_into::<HtmlOptionElement>().ok()) {
+                option.set_selected(values.contains(&option.value()));
+            }
+        }
+    } else if let Some(textarea) = input.dyn_ref::<HtmlTextAreaElement>() {
+        textarea.set_value(&values.join("\n"));
+    } else if let Some(input) = input.dyn_ref::<HtmlInputElement>() {
+        input.set_value(&values[0]);
+    }
+    Ok(())
+}
+
+/// The values entered in the inputs of `render_form`, in the order of `fields`.
+fn read_form(console: &Element, fields: &[FormField]) -> Result<Vec<FieldValue>, JsValue> {
+    let mut values = vec![FieldValue::Text(Vec::new()); fields.len()];
+    for input in dom::query_all(console, "[data-form-field]")? {
+        let Some(index) = input.get_attribute("data-form-field").and_then(|index| index.parse::<usize>().ok()) else {
+            continue;
+        };
+        if let Some(value) = fields.get(index).and_then(|field| read_form_input(&input, field)) {
+            values[index] = value;
+        }
+    }
+    Ok(values)
+}
+
+fn read_form_input(input: &Element, field: &FormField) -> Option<FieldValue> {
+    Some(if let Some(select) = input.dyn_ref::<HtmlSelectElement>() {
+        let selected = select.selected_options();
+        FieldValue::Text(
+            (0..selected.length())
+                .filter_map(|i| selected.item(i))
+                .filter_map(|option| option.dyn_into::<HtmlOptionElement>().ok())
+                .map(|option| option.value())
+                .collect(),
+        )
+    } else if let Some(textarea) = input.dyn_ref::<HtmlTextAreaElement>() {
+        let text = textarea.value();
+        if field.kind == FieldKind::Json {
+            FieldValue::Text(vec![text])
+        } else {
+            FieldValue::Text(text.lines().map(|line| line.trim().to_string()).collect())
+        }
+    } else {
+        let input = input.dyn_ref::<HtmlInputElement>()?;
+        match input.files() {
+            Some(files) if field.kind == FieldKind::File => {
+                FieldValue::Files((0..files.length()).filter_map(|i| files.item(i)).collect())
+            }
+            _ => FieldValue::Text(vec![input.value()]),
+        }
+    })
+}
+
+fn violations(spec: &Spec, schema: &Schema, value: &serde_json::Value) -> Vec<String> {
+    validate(spec, schema, value).iter().map(ToString::to_string).collect()
+}
+
+/// Mark `problems` next to their inputs.
+fn show_problems(document: &Document, problems: &[(Element, Vec<String>)]) -> Result<(), JsValue> {
+    for (input, messages) in problems {
+        dom::add_classes(input, "try-it-out-invalid")?;
+        let message = dom::text(document, "div", "try-it-out-problem", &messages.join("\n"))?;
+        dom::insert_after(input, &message)?;
+    }
+    Ok(())
+}
+
+fn clear_problems(console: &Element) -> Result<(), JsValue> {
+    for message in dom::query_all(console, ".try-it-out-problem")? {
+        message.remove();
+    }
+    for input in dom::query_all(console, ".try-it-out-invalid")? {
+        dom::remove_classes(&input, "try-it-out-invalid")?;
+    }
+    Ok(())
+}
+
+/// Split `Name: value` lines into headers and the cookies of any `Cookie` line.
+fn parse_headers(text: &str) -> (Pairs, Pairs) {
+    let mut headers = Vec::new();
+    let mut cookies = Vec::new();
+    for line in text.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim());
+        if name.eq_ignore_ascii_case("cookie") {
+            cookies.extend(
+                value
+                    .split(';')
+                    .filter_map(|cookie| cookie.split_once('='))
+                    .map(|(name, value)| (name.trim().to_string(), value.trim().to_string())),
+            );
+        } else if !name.is_empty() {
+            headers.push((name.to_string(), value.to_string()));
+        }
+    }
+    (headers, cookies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+    use pretty_assertions::assert_eq;
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_parse_headers() {
+        let (headers, cookies) = parse_headers("Accept: application/json\nnot a header\nCookie: a=1; b=2\n");
+
+        assert_eq!(vec![("Accept".to_string(), "application/json".to_string())], headers);
+        assert_eq!(
+            vec![("a".to_string(), "1".to_string()), ("b".to_string(), "2".to_string())],
+            cookies
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn test_scroll_offset() {
+        let offset = |json: &str| ScrollOffset::of(&serde_json::from_str::<RedocOptions>(json).unwrap());
+
+        assert_eq!(ScrollOffset::Pixels(0.0), offset("{}"));
+        assert_eq!(ScrollOffset::Pixels(60.0), offset(r#"{"scrollYOffset": "60px"}"#));
+        assert_eq!(
+            ScrollOffset::Below("header.navbar".to_string()),
+            offset(r#"{"selector": "header.navbar"}"#)
+        );
+    }
+}